@@ -1,6 +1,6 @@
 use super::subcommands::*;
 use crate::TblCliError;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use color_print::cstr;
 use std::path::PathBuf;
 
@@ -21,6 +21,11 @@ pub(crate) async fn run_cli() -> Result<(), TblCliError> {
         Some(Subcommands::Ls(args)) => ls_command(args).await,
         Some(Subcommands::Schema(args)) => schema_command(args).await,
         Some(Subcommands::Schemas(args)) => schemas_command(args).await,
+        Some(Subcommands::Check(args)) => check_command(args).await,
+        Some(Subcommands::Coverage(args)) => coverage_command(args).await,
+        Some(Subcommands::Index(args)) => index_command(args).await,
+        Some(Subcommands::Inspect(args)) => inspect_command(args).await,
+        Some(Subcommands::Find(args)) => find_command(args).await,
         _ => data_command(args.data_args).await,
     }
 }
@@ -31,10 +36,10 @@ pub(crate) async fn run_cli() -> Result<(), TblCliError> {
     author,
     about = cstr!("<white><bold>tbl</bold></white> is a tool for reading and editing tabular data files"),
     override_usage = cstr!("<white><bold>tbl</bold></white> has two modes
-1. Summary mode: <white><bold>tbl [ls | schema] [SUMMARY_OPTIONS]</bold></white>
+1. Summary mode: <white><bold>tbl [ls | schema | check] [SUMMARY_OPTIONS]</bold></white>
 2. Data mode:    <white><bold>tbl [DATA_OPTIONS]</bold></white>
 
-Get help with <white><bold>SUMMARY_OPTIONS</bold></white> using <white><bold>tbl [ls | schema] -h</bold></white>
+Get help with <white><bold>SUMMARY_OPTIONS</bold></white> using <white><bold>tbl [ls | schema | check] -h</bold></white>
 
 Data mode is the default mode. <white><bold>DATA_OPTIONS</bold></white> are documented below
 "),
@@ -43,7 +48,9 @@ Data mode is the default mode. <white><bold>DATA_OPTIONS</bold></white> are docu
 <white><bold>2.</bold></white> modify each file <white><bold>inplace</bold></white>        <white><bold>--inplace</bold></white>
 <white><bold>3.</bold></white> copy files into a <white><bold>new dir</bold></white>       <white><bold>--output-dir</bold></white> /path/to/dir
 <white><bold>4.</bold></white> load as <white><bold>interactive</bold></white> python      <white><bold>--df | --lf</bold></white>
-<white><bold>5.</bold></white> output data to <white><bold>stdout</bold></white>           (default behavior)"),
+<white><bold>5.</bold></white> output data to <white><bold>stdout</bold></white>           (default behavior)
+<white><bold>6.</bold></white> write <white><bold>hive partitions</bold></white>        <white><bold>--partition</bold></white> COLUMN --output-dir /path/to/dir
+<white><bold>7.</bold></white> browse in <white><bold>terminal viewer</bold></white>      <white><bold>--tui</bold></white>"),
     long_about = None,
     disable_help_subcommand = true,
     disable_help_flag = true,
@@ -87,6 +94,21 @@ pub(crate) enum Subcommands {
     #[command(hide = true)]
     Schemas(SchemasArgs),
 
+    /// Check integrity of tabular files
+    Check(CheckArgs),
+
+    /// Detect missing and overlapping ranges of range-named files
+    Coverage(CoverageArgs),
+
+    /// Write a parquet manifest describing each file of a dataset
+    Index(IndexArgs),
+
+    /// Display row groups and column chunks of a parquet file
+    Inspect(InspectArgs),
+
+    /// Find rows matching a value, reading only row groups that may contain it
+    Find(FindArgs),
+
     /// Load, transform, and output file data [default subcommand]
     #[command(hide = true)]
     Data,
@@ -107,6 +129,9 @@ pub(crate) struct LsArgs {
     #[clap(short, long)]
     pub(crate) tree: bool,
 
+    #[clap(flatten)]
+    pub(crate) inputs: InputArgs,
+
     /// show absolute paths instead of relative
     #[clap(long)]
     pub(crate) absolute: bool,
@@ -123,6 +148,10 @@ pub(crate) struct LsArgs {
     #[clap(long)]
     pub(crate) n: Option<usize>,
 
+    /// reuse summaries of unchanged files from cache
+    #[clap(long)]
+    pub(crate) cache: bool,
+
     /// sort by number of rows, files, or bytes
     #[clap(long, default_value = "bytes")]
     pub(crate) sort: String,
@@ -143,6 +172,9 @@ pub(crate) struct SchemaArgs {
     #[clap(short, long)]
     pub(crate) tree: bool,
 
+    #[clap(flatten)]
+    pub(crate) inputs: InputArgs,
+
     /// display bytes stats
     #[clap(long, hide = true)]
     pub(crate) bytes: bool,
@@ -167,6 +199,10 @@ pub(crate) struct SchemaArgs {
     #[clap(long)]
     pub(crate) absolute: bool,
 
+    /// reuse summaries of unchanged files from cache
+    #[clap(long)]
+    pub(crate) cache: bool,
+
     /// sort by number of rows, files, or bytes
     #[clap(long, default_value = "bytes")]
     pub(crate) sort: String,
@@ -192,6 +228,248 @@ pub(crate) struct SchemasArgs {
     pub(crate) sort: String,
 }
 
+/// Arguments for the `check` subcommand
+#[derive(Clone, Parser)]
+pub(crate) struct CheckArgs {
+    /// display help message
+    #[clap(short, long, action = clap::ArgAction::HelpLong, help_heading = "General Options")]
+    help: Option<bool>,
+
+    /// input path(s) to use
+    #[clap()]
+    pub(crate) paths: Option<Vec<PathBuf>>,
+
+    /// recursively check all files in tree
+    #[clap(short, long)]
+    pub(crate) tree: bool,
+
+    #[clap(flatten)]
+    pub(crate) inputs: InputArgs,
+
+    /// only decode the first and last row group of each file
+    #[clap(long)]
+    pub(crate) quick: bool,
+
+    /// check that rows are sorted and unique by these key column(s)
+    #[clap(long, value_name = "COLUMN", num_args(1..))]
+    pub(crate) key: Option<Vec<String>>,
+
+    /// allow rows with duplicate keys when using --key
+    #[clap(long)]
+    pub(crate) allow_duplicates: bool,
+
+    /// maximum number of key violations to print
+    #[clap(long, default_value_t = 10)]
+    pub(crate) max_violations: usize,
+
+    /// show absolute paths instead of relative
+    #[clap(long)]
+    pub(crate) absolute: bool,
+}
+
+/// Arguments for the `coverage` subcommand
+#[derive(Clone, Parser)]
+pub(crate) struct CoverageArgs {
+    /// display help message
+    #[clap(short, long, action = clap::ArgAction::HelpLong, help_heading = "General Options")]
+    help: Option<bool>,
+
+    /// input path(s) to use
+    #[clap()]
+    pub(crate) paths: Option<Vec<PathBuf>>,
+
+    /// recursively use all files in tree
+    #[clap(short, long)]
+    pub(crate) tree: bool,
+
+    #[clap(flatten)]
+    pub(crate) inputs: InputArgs,
+
+    /// check min and max statistics of this column against file ranges
+    #[clap(long, value_name = "COLUMN")]
+    pub(crate) column: Option<String>,
+
+    /// take ranges from column statistics instead of file names
+    #[clap(long)]
+    pub(crate) from_stats: bool,
+
+    /// show absolute paths instead of relative
+    #[clap(long)]
+    pub(crate) absolute: bool,
+}
+
+/// Arguments for the `index` subcommand
+#[derive(Clone, Parser)]
+pub(crate) struct IndexArgs {
+    /// display help message
+    #[clap(short, long, action = clap::ArgAction::HelpLong, help_heading = "General Options")]
+    help: Option<bool>,
+
+    /// input path(s) to use
+    #[clap()]
+    pub(crate) paths: Option<Vec<PathBuf>>,
+
+    /// recursively index all files in tree
+    #[clap(short, long)]
+    pub(crate) tree: bool,
+
+    #[clap(flatten)]
+    pub(crate) inputs: InputArgs,
+
+    /// path of manifest file to write
+    #[clap(short, long, value_name = "PATH", default_value = "tbl_index.parquet")]
+    pub(crate) output: PathBuf,
+}
+
+/// Arguments for the `inspect` subcommand
+#[derive(Clone, Parser)]
+pub(crate) struct InspectArgs {
+    /// display help message
+    #[clap(short, long, action = clap::ArgAction::HelpLong, help_heading = "General Options")]
+    help: Option<bool>,
+
+    /// parquet file to inspect
+    #[clap()]
+    pub(crate) path: PathBuf,
+
+    /// only show this row group
+    #[clap(long, value_name = "N")]
+    pub(crate) row_group: Option<usize>,
+
+    /// only show these columns
+    #[clap(long, value_name = "COLUMN", num_args(1..))]
+    pub(crate) columns: Option<Vec<String>>,
+
+    /// show key-value metadata values without truncating
+    #[clap(long)]
+    pub(crate) full_metadata: bool,
+}
+
+/// Arguments for the `find` subcommand
+#[derive(Clone, Parser)]
+pub(crate) struct FindArgs {
+    /// display help message
+    #[clap(short, long, action = clap::ArgAction::HelpLong, help_heading = "General Options")]
+    help: Option<bool>,
+
+    /// value to look up, such as block_number=1000
+    #[clap(value_name = "COLUMN=VALUE")]
+    pub(crate) query: String,
+
+    /// input path(s) to use
+    #[clap()]
+    pub(crate) paths: Option<Vec<PathBuf>>,
+
+    /// recursively search all files in tree
+    #[clap(short, long)]
+    pub(crate) tree: bool,
+
+    #[clap(flatten)]
+    pub(crate) inputs: InputArgs,
+
+    /// only show these columns
+    #[clap(long, value_name = "COLUMN", num_args(1..))]
+    pub(crate) columns: Option<Vec<String>>,
+
+    /// number of rows to print, or "all"
+    #[clap(short, long, value_name = "N")]
+    pub(crate) n: Option<String>,
+
+    /// print rows as json
+    #[clap(long)]
+    pub(crate) json: bool,
+
+    /// maximum number of files to read at once
+    #[clap(long, value_name = "N", default_value_t = tbl_core::parquet::DEFAULT_MAX_CONCURRENT)]
+    pub(crate) max_concurrent: usize,
+}
+
+/// Arguments for selecting input files, shared by subcommands
+#[derive(Clone, Parser)]
+pub(crate) struct InputArgs {
+    /// read input paths from file, one per line or NUL-separated
+    #[clap(
+        long,
+        help = cstr!("read input paths from <white><bold>PATH</bold></white>, or stdin if <white><bold>-</bold></white>"),
+        help_heading = "Input Options",
+        value_name = "PATH"
+    )]
+    pub(crate) files_from: Option<PathBuf>,
+
+    /// read input paths from manifest written by `tbl index`
+    #[clap(
+        long,
+        help = cstr!("read input paths from manifest <white><bold>PATH</bold></white> written by <white><bold>tbl index</bold></white>"),
+        help_heading = "Input Options",
+        value_name = "PATH"
+    )]
+    pub(crate) index: Option<PathBuf>,
+
+    /// paths read from --files-from or --index, so that stdin is only consumed once
+    #[clap(skip)]
+    pub(crate) listed_paths: Option<Vec<PathBuf>>,
+
+    /// skip paths matching glob pattern(s)
+    #[clap(
+        long,
+        help = cstr!("skip files or directories matching glob <white><bold>PATTERN</bold></white>(s)"),
+        help_heading = "Input Options",
+        value_name = "PATTERN",
+        num_args(1..)
+    )]
+    pub(crate) exclude: Option<Vec<String>>,
+
+    /// maximum directory depth to descend into
+    #[clap(
+        long,
+        help = cstr!("maximum directory depth to descend, <white><bold>1</bold></white> for only direct children"),
+        help_heading = "Input Options",
+        value_name = "N"
+    )]
+    pub(crate) max_depth: Option<usize>,
+
+    /// do not follow symbolic links when walking directories
+    #[clap(long, help_heading = "Input Options")]
+    pub(crate) no_follow_symlinks: bool,
+
+    /// skip hidden files and directories
+    #[clap(long, help_heading = "Input Options")]
+    pub(crate) no_hidden: bool,
+
+    /// select only files with at least this many rows
+    #[clap(long, help_heading = "Input Options", value_name = "N")]
+    pub(crate) min_rows: Option<u64>,
+
+    /// select only files with at most this many rows
+    #[clap(long, help_heading = "Input Options", value_name = "N")]
+    pub(crate) max_rows: Option<u64>,
+
+    /// select only files with at least this many bytes
+    #[clap(long, help_heading = "Input Options", value_name = "BYTES")]
+    pub(crate) min_bytes: Option<u64>,
+
+    /// select only files modified since a time
+    #[clap(
+        long,
+        help = cstr!("select only files modified since <white><bold>TIME</bold></white>, e.g. 2024-06-01 or 7d"),
+        help_heading = "Input Options",
+        value_name = "TIME"
+    )]
+    pub(crate) modified_since: Option<String>,
+
+    /// select only files that have these column(s)
+    #[clap(long, help_heading = "Input Options", value_name = "COLUMN", num_args(1..))]
+    pub(crate) has_column: Option<Vec<String>>,
+
+    /// select only files that lack these column(s)
+    #[clap(long, help_heading = "Input Options", value_name = "COLUMN", num_args(1..))]
+    pub(crate) missing_column: Option<Vec<String>>,
+
+    /// select only files with the same schema as this file
+    #[clap(long, help_heading = "Input Options", value_name = "FILE")]
+    pub(crate) schema_matches: Option<PathBuf>,
+}
+
 /// Arguments for the `data` subcommand
 #[derive(Clone, Parser)]
 pub(crate) struct DataArgs {
@@ -210,6 +488,19 @@ pub(crate) struct DataArgs {
     #[clap(short, long, verbatim_doc_comment, help_heading = "Input Options")]
     pub(crate) tree: bool,
 
+    #[clap(flatten)]
+    pub(crate) inputs: InputArgs,
+
+    /// format of data piped on stdin with input path -
+    #[clap(
+        long,
+        help = cstr!("format of data piped on stdin with input path <white><bold>-</bold></white>,
+    one of csv, json, jsonl, parquet, ipc [default: detected]"),
+        help_heading = "Input Options",
+        value_name = "FORMAT"
+    )]
+    pub(crate) input_format: Option<String>,
+
     //
     // // transform options
     //
@@ -299,6 +590,26 @@ pub(crate) struct DataArgs {
     )]
     pub(crate) filter: Option<Vec<String>>,
 
+    /// remove duplicate rows, comparing only COLUMNs if given
+    #[clap(
+        long,
+        help = cstr!("remove duplicate rows, comparing only <white><bold>COLUMN</bold></white>s if given"),
+        help_heading = "Transform Options",
+        value_name = "COLUMN",
+        num_args(0..)
+    )]
+    pub(crate) unique: Option<Vec<String>>,
+
+    /// which duplicate row to keep with --unique
+    #[clap(
+        long,
+        help = cstr!("which duplicate to keep with <white><bold>--unique</bold></white>"),
+        help_heading = "Transform Options",
+        default_value_t = UniqueKeep::First,
+        value_name = "KEEP"
+    )]
+    pub(crate) keep: UniqueKeep,
+
     /// sort rows, syntax COLUMN[:desc]
     #[clap(
         short,
@@ -326,6 +637,31 @@ pub(crate) struct DataArgs {
     #[clap(long, help_heading = "Transform Options")]
     pub(crate) offset: Option<usize>,
 
+    /// keep a random sample of n rows across all files
+    #[clap(long, help_heading = "Transform Options", value_name = "N")]
+    pub(crate) sample: Option<u64>,
+
+    /// keep a random fraction of rows across all files
+    #[clap(long, help_heading = "Transform Options", value_name = "FRACTION")]
+    pub(crate) sample_frac: Option<f64>,
+
+    /// sample separately within each value of a column
+    #[clap(
+        long,
+        help = cstr!("sample separately within each value of <white><bold>COLUMN</bold></white>"),
+        help_heading = "Transform Options",
+        value_name = "COLUMN"
+    )]
+    pub(crate) sample_by: Option<String>,
+
+    /// random seed for sampling
+    #[clap(
+        long,
+        help = cstr!("random seed for <white><bold>--sample</bold></white> or <white><bold>--sample-frac</bold></white>"),
+        help_heading = "Transform Options"
+    )]
+    pub(crate) seed: Option<u64>,
+
     /// compute value counts of column(s)
     #[clap(long, help_heading = "Transform Options", value_name = "COLUMN")]
     pub(crate) value_counts: Option<String>,
@@ -358,6 +694,39 @@ pub(crate) struct DataArgs {
     #[clap(long, help_heading = "Output Options")]
     pub(crate) jsonl: bool,
 
+    /// output format for stdout
+    #[clap(
+        long,
+        help = cstr!("stdout format, one of table, csv, tsv, json, jsonl, markdown,
+    plain, sql, ipc, parquet [default: table]"),
+        help_heading = "Output Options",
+        value_name = "FORMAT"
+    )]
+    pub(crate) format: Option<String>,
+
+    /// table name of sql insert statements
+    #[clap(
+        long,
+        help = cstr!("table name for <white><bold>--format sql</bold></white>"),
+        help_heading = "Output Options",
+        default_value = "data",
+        value_name = "TABLE"
+    )]
+    pub(crate) sql_table: String,
+
+    /// output data as an arrow ipc stream [alias --arrow]
+    #[clap(
+        long,
+        help = cstr!("output data as an arrow ipc stream [alias <white><bold>--arrow</bold></white>]"),
+        help_heading = "Output Options",
+        aliases = ["arrow"]
+    )]
+    pub(crate) ipc: bool,
+
+    /// output data as parquet
+    #[clap(long, help_heading = "Output Options")]
+    pub(crate) parquet: bool,
+
     /// encode binary columns as hex for output
     #[clap(long, help_heading = "Output Options")]
     pub(crate) hex: bool,
@@ -382,14 +751,97 @@ pub(crate) struct DataArgs {
     #[clap(long, help_heading = "Output Options", value_name = "POST-FIX")]
     pub(crate) output_postfix: Option<String>,
 
-    /// partition output over this column
+    /// file format of written outputs
+    #[clap(
+        long,
+        help = cstr!("format of output files, one of parquet, csv, tsv, json, jsonl, ipc
+    [default: from file extension]"),
+        help_heading = "Output Options",
+        value_name = "FORMAT"
+    )]
+    pub(crate) output_format: Option<String>,
+
+    /// field delimiter of csv output
+    #[clap(long, help_heading = "Output Options", value_name = "CHAR")]
+    pub(crate) delimiter: Option<char>,
+
+    /// omit header row of csv and tsv output
+    #[clap(long, help_heading = "Output Options")]
+    pub(crate) no_header: bool,
+
+    /// strftime format of datetime values in csv output
+    #[clap(long, help_heading = "Output Options", value_name = "FORMAT")]
+    pub(crate) datetime_format: Option<String>,
+
+    /// strftime format of date values in csv output
+    #[clap(long, help_heading = "Output Options", value_name = "FORMAT")]
+    pub(crate) date_format: Option<String>,
+
+    /// set key-value metadata entries of parquet output
+    #[clap(
+        long,
+        help_heading = "Output Options",
+        value_name = "KEY=VALUE",
+        num_args(1..)
+    )]
+    pub(crate) set_metadata: Option<Vec<String>>,
+
+    /// drop key-value metadata entries of parquet output
+    #[clap(
+        long,
+        help_heading = "Output Options",
+        value_name = "KEY",
+        num_args(1..)
+    )]
+    pub(crate) drop_metadata: Option<Vec<String>>,
+
+    /// compression codec of parquet output
+    #[clap(
+        long,
+        help = cstr!("compression of parquet output, one of uncompressed, snappy, gzip,
+    brotli, zstd, lz4 [default: zstd]"),
+        help_heading = "Output Options",
+        value_name = "CODEC"
+    )]
+    pub(crate) compression: Option<String>,
+
+    /// number of rows per row group of parquet output
+    #[clap(long, help_heading = "Output Options", value_name = "ROWS")]
+    pub(crate) row_group_size: Option<usize>,
+
+    /// write bloom filters for these columns of parquet output
     #[clap(
         long,
+        help = cstr!("write parquet bloom filters for <white><bold>COLUMN</bold></white>(s), along with page indexes"),
         help_heading = "Output Options",
         value_name = "COLUMN",
-        hide = true
+        num_args(1..)
+    )]
+    pub(crate) bloom_filter: Option<Vec<String>>,
+
+    /// write column and offset page indexes of parquet output
+    #[clap(long, help_heading = "Output Options")]
+    pub(crate) page_index: bool,
+
+    /// keep layout of each rewritten parquet file
+    #[clap(
+        long,
+        help = cstr!("keep compression, row group size, sort order, column order, and
+    field metadata of each file rewritten by <white><bold>--inplace</bold></white> or <white><bold>--output-dir</bold></white>"),
+        help_heading = "Output Options"
     )]
-    pub(crate) partition: Option<String>,
+    pub(crate) preserve: bool,
+
+    /// write output as hive partitions over these column(s)
+    #[clap(
+        long,
+        help = cstr!("write output as hive partitions over <white><bold>COLUMN</bold></white>(s),
+    into <white><bold>--output-dir</bold></white> or current directory"),
+        help_heading = "Output Options",
+        value_name = "COLUMN",
+        num_args(1..)
+    )]
+    pub(crate) partition: Option<Vec<String>>,
 
     /// partition mode, by range of values per partition
     #[clap(
@@ -426,6 +878,15 @@ pub(crate) struct DataArgs {
     #[clap(long, help_heading = "Output Options")]
     pub(crate) lf: bool,
 
+    /// browse data in a full-screen terminal viewer [alias --view]
+    #[clap(
+        long,
+        help = cstr!("browse data in a full-screen terminal viewer [alias <white><bold>--view</bold></white>]"),
+        help_heading = "Output Options",
+        aliases = ["view"]
+    )]
+    pub(crate) tui: bool,
+
     /// python executable to use with --df or --lf
     #[clap(
         long,
@@ -442,3 +903,22 @@ pub(crate) struct DataArgs {
     #[clap(long, help_heading = "Output Options")]
     pub(crate) dry: bool,
 }
+
+/// which row of each group of duplicates --unique keeps
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum UniqueKeep {
+    First,
+    Last,
+    None,
+}
+
+impl std::fmt::Display for UniqueKeep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            UniqueKeep::First => "first",
+            UniqueKeep::Last => "last",
+            UniqueKeep::None => "none",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::styles::FontStyle;
+use crate::{CheckArgs, TblCliError};
+use std::path::PathBuf;
+use tbl_core::formats::{format_bytes, format_with_commas};
+use tbl_core::parquet::{KeyCheck, ParquetCheck, ParquetIssue};
+use toolstr::Colorize;
+
+pub(crate) async fn check_command(args: CheckArgs) -> Result<(), TblCliError> {
+    // get paths
+    let paths = crate::inputs::get_input_paths(&args.paths, args.tree, &args.inputs).await?;
+    if paths.is_empty() {
+        println!("[no tabular paths]");
+        return Ok(());
+    }
+
+    // check files
+    let checks = tbl_core::parquet::check_parquet_files(&paths, args.quick).await?;
+
+    // print results
+    let display_paths = get_display_paths(&paths, args.absolute)?;
+    let n_bad = print_checks(&checks, &display_paths);
+    print_check_totals(&checks, n_bad);
+
+    // check key ordering and uniqueness of files that can be decoded
+    let key_ok = match args.key.as_ref() {
+        Some(key) => {
+            let decodable: Vec<PathBuf> = checks
+                .iter()
+                .filter(|check| {
+                    !check.issues.iter().any(|issue| {
+                        matches!(
+                            issue,
+                            ParquetIssue::UnreadableFooter(_) | ParquetIssue::DecodeError { .. }
+                        )
+                    })
+                })
+                .map(|check| check.path.clone())
+                .collect();
+            let key_check = tbl_core::parquet::check_parquet_key_order(
+                &decodable,
+                key,
+                !args.allow_duplicates,
+                args.max_violations,
+            )
+            .await?;
+            println!();
+            print_key_check(&key_check, key, &paths, &display_paths);
+            key_check.is_ok()
+        }
+        None => true,
+    };
+
+    if n_bad > 0 || !key_ok {
+        std::process::exit(1)
+    }
+
+    Ok(())
+}
+
+fn get_display_paths(paths: &[PathBuf], absolute: bool) -> Result<Vec<PathBuf>, TblCliError> {
+    if absolute || (paths.len() == 1) {
+        Ok(paths.to_vec())
+    } else {
+        let common_prefix = tbl_core::filesystem::get_common_prefix(paths)?;
+        let mut new_paths = Vec::new();
+        for path in paths.iter() {
+            new_paths.push(path.strip_prefix(&common_prefix)?.to_owned())
+        }
+        Ok(new_paths)
+    }
+}
+
+fn print_checks(checks: &[ParquetCheck], display_paths: &[PathBuf]) -> usize {
+    let mut n_bad = 0;
+    for (check, path) in checks.iter().zip(display_paths.iter()) {
+        if check.is_ok() {
+            continue;
+        }
+        n_bad += 1;
+        println!("{}", path.to_string_lossy().purple());
+        for issue in check.issues.iter() {
+            println!(
+                "    {} {}",
+                "-".colorize_title(),
+                issue.to_string().as_str().colorize_comment()
+            );
+        }
+    }
+    if n_bad > 0 {
+        println!();
+    }
+    n_bad
+}
+
+fn print_check_totals(checks: &[ParquetCheck], n_bad: usize) {
+    let n_rows: u64 = checks.iter().map(|check| check.n_rows).sum();
+    let n_bytes: u64 = checks.iter().map(|check| check.n_bytes).sum();
+    let n_row_groups: usize = checks.iter().map(|check| check.n_row_groups_decoded).sum();
+    println!(
+        "checked {} files, {} row groups, {} rows, {}",
+        format_with_commas(checks.len() as u64).green().bold(),
+        format_with_commas(n_row_groups as u64).green().bold(),
+        format_with_commas(n_rows).green().bold(),
+        format_bytes(n_bytes).green().bold(),
+    );
+    if n_bad == 0 {
+        println!("{}", "no problems found".green().bold());
+    } else {
+        println!(
+            "{} files with problems",
+            format_with_commas(n_bad as u64).red().bold()
+        );
+    }
+}
+
+fn print_key_check(
+    key_check: &KeyCheck,
+    key: &[String],
+    paths: &[PathBuf],
+    display_paths: &[PathBuf],
+) {
+    let display = |path: &PathBuf| -> String {
+        paths
+            .iter()
+            .position(|p| p == path)
+            .map(|i| display_paths[i].to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    };
+
+    for violation in key_check.violations.iter() {
+        println!(
+            "{} {} row {}, previous row: {} row {}",
+            format!("{}:", violation.kind).as_str().colorize_title(),
+            display(&violation.path).purple(),
+            format_with_commas(violation.row)
+                .as_str()
+                .colorize_constant(),
+            display(&violation.previous_path).purple(),
+            format_with_commas(violation.previous_row)
+                .as_str()
+                .colorize_constant(),
+        );
+    }
+    if !key_check.violations.is_empty() {
+        println!();
+    }
+
+    let key_str = key.join(", ");
+    if key_check.is_ok() {
+        println!(
+            "{} rows are sorted by ({})",
+            format_with_commas(key_check.n_rows).green().bold(),
+            key_str,
+        );
+    } else {
+        println!(
+            "{} rows checked by ({}), {} out of order, {} duplicates",
+            format_with_commas(key_check.n_rows).green().bold(),
+            key_str,
+            format_with_commas(key_check.n_out_of_order).red().bold(),
+            format_with_commas(key_check.n_duplicates).red().bold(),
+        );
+    }
+}
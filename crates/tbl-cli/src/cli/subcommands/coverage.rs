@@ -0,0 +1,122 @@
+use crate::styles::FontStyle;
+use crate::{CoverageArgs, TblCliError};
+use std::path::{Path, PathBuf};
+use tbl_core::formats::{format_with_commas, print_header};
+use tbl_core::parquet::CoverageReport;
+use toolstr::Colorize;
+
+pub(crate) async fn coverage_command(args: CoverageArgs) -> Result<(), TblCliError> {
+    // get paths
+    let paths = crate::inputs::get_input_paths(&args.paths, args.tree, &args.inputs).await?;
+    if paths.is_empty() {
+        println!("[no tabular paths]");
+        return Ok(());
+    }
+
+    // compute coverage
+    let report =
+        tbl_core::parquet::get_coverage_report(&paths, args.column.as_deref(), args.from_stats)
+            .await?;
+
+    // print report
+    let common_prefix = if args.absolute || (paths.len() == 1) {
+        PathBuf::new()
+    } else {
+        tbl_core::filesystem::get_common_prefix(&paths)?
+    };
+    print_coverage_report(&report, &common_prefix);
+
+    if !report.gaps.is_empty() || !report.overlaps.is_empty() || !report.violations.is_empty() {
+        std::process::exit(1)
+    }
+
+    Ok(())
+}
+
+fn display_path(path: &Path, common_prefix: &Path) -> String {
+    path.strip_prefix(common_prefix)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn print_coverage_report(report: &CoverageReport, common_prefix: &Path) {
+    if !report.gaps.is_empty() {
+        print_header("Missing ranges");
+        for gap in report.gaps.iter() {
+            println!(
+                "{} {} to {} ({} values)",
+                gap.name.as_str().colorize_function(),
+                gap.start.to_string().as_str().colorize_constant(),
+                gap.end.to_string().as_str().colorize_constant(),
+                format_with_commas(gap.end - gap.start + 1),
+            );
+        }
+        println!();
+    }
+
+    if !report.overlaps.is_empty() {
+        print_header("Overlapping ranges");
+        for overlap in report.overlaps.iter() {
+            println!(
+                "{} {} to {}",
+                overlap.name.as_str().colorize_function(),
+                overlap.start.to_string().as_str().colorize_constant(),
+                overlap.end.to_string().as_str().colorize_constant(),
+            );
+            for path in [&overlap.first, &overlap.second] {
+                println!(
+                    "    {} {}",
+                    "-".colorize_title(),
+                    display_path(path, common_prefix).purple()
+                );
+            }
+        }
+        println!();
+    }
+
+    if !report.violations.is_empty() {
+        print_header("Files with values outside of declared range");
+        for violation in report.violations.iter() {
+            println!(
+                "{} declares {} to {}, contains {} to {}",
+                display_path(&violation.path, common_prefix).purple(),
+                violation.start.to_string().as_str().colorize_constant(),
+                violation.end.to_string().as_str().colorize_constant(),
+                violation.min.to_string().as_str().colorize_constant(),
+                violation.max.to_string().as_str().colorize_constant(),
+            );
+        }
+        println!();
+    }
+
+    if !report.unchecked.is_empty() {
+        print_header("Files whose statistics could not be checked");
+        for path in report.unchecked.iter() {
+            println!("{}", display_path(path, common_prefix).purple());
+        }
+        println!();
+    }
+
+    if !report.unknown.is_empty() {
+        print_header("Files without a range");
+        for path in report.unknown.iter() {
+            println!("{}", display_path(path, common_prefix).purple());
+        }
+        println!();
+    }
+
+    println!(
+        "{} ranged files, {} missing ranges, {} overlapping ranges, {} files out of range",
+        format_with_commas(report.ranges.len() as u64)
+            .green()
+            .bold(),
+        format_with_commas(report.gaps.len() as u64).green().bold(),
+        format_with_commas(report.overlaps.len() as u64)
+            .green()
+            .bold(),
+        format_with_commas(report.violations.len() as u64)
+            .green()
+            .bold(),
+    );
+}
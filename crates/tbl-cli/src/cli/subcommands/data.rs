@@ -1,32 +1,120 @@
-use crate::{DataArgs, OutputMode, TblCliError};
+use crate::inputs::{get_input_paths, get_input_spec, is_stdin_path, read_stdin_lazyframe};
+use crate::{DataArgs, OutputFileFormat, OutputMode, TblCliError};
+use polars::prelude::*;
+use std::collections::HashSet;
 use std::path::PathBuf;
-use tbl_core::filesystem::{get_input_paths, get_output_paths, OutputPathSpec};
+use tbl_core::filesystem::{get_hive_roots, get_output_paths, is_glob_pattern};
+use tbl_core::filesystem::{is_remote_path, prune_hive_paths, select_input_paths, OutputPathSpec};
+use tbl_core::filters::{parse_column_filter, ColumnFilter};
+use tbl_core::parquet::{get_added_hive_columns, prune_paths_by_statistics, select_parquets_rows};
+use tbl_core::parquet::{prune_paths_by_index, read_parquet_index};
+use tbl_core::parquet::{ParquetRowSelection, PruneSummary, SampleSize, DEFAULT_MAX_CONCURRENT};
 
-pub(crate) async fn data_command(args: DataArgs) -> Result<(), TblCliError> {
+pub(crate) async fn data_command(mut args: DataArgs) -> Result<(), TblCliError> {
     inquire::set_global_render_config(crate::styles::get_render_config());
 
+    // fix sampling seed so that it can be reported and reused
+    if get_sample_size(&args)?.is_some() && args.seed.is_none() {
+        args.seed = Some(random_seed());
+    }
+
+    // read --files-from once, since stdin cannot be read again
+    args.inputs.listed_paths = crate::inputs::get_listed_paths(&args.inputs)?;
+
+    // translate --format into output flags
+    apply_format(&mut args)?;
+
+    // validate metadata edits before any file is written
+    crate::output::parse_set_metadata(&args)?;
+
     // decide output mode
     let output_mode = decide_output_mode(&args)?;
 
-    // create input output pairs
-    let io = gather_inputs_and_outputs(&output_mode, &args)?;
+    // validate parquet write options
+    if let Some(compression) = &args.compression {
+        crate::output::parse_compression(compression)?;
+    }
+    if args.preserve
+        && !matches!(
+            output_mode,
+            OutputMode::ModifyInplace | OutputMode::SaveToDirectory
+        )
+    {
+        return Err(TblCliError::Arg(
+            "--preserve requires --inplace or --output-dir".to_string(),
+        ));
+    }
+
+    // binary stdout output cannot be interleaved with a summary
+    if matches!(output_mode, OutputMode::PrintToStdout) && (args.ipc | args.parquet) {
+        args.no_summary = true;
+    }
+
+    // create input output pairs, hive partitions are parsed below the input directories
+    let hive_roots = get_hive_roots(&get_input_spec(&args.paths, args.tree, &args.inputs)?)?;
+    let io = gather_inputs_and_outputs(&output_mode, &hive_roots, &args).await?;
+
+    // metadata edits are only written into parquet output files
+    if args.set_metadata.is_some() || args.drop_metadata.is_some() {
+        validate_metadata_outputs(&io, &output_mode, &args)?;
+    }
+
+    // skip files whose statistics rule out every row
+    let (io, pruning, lookups) = prune_io_by_statistics(io, &output_mode, &args).await?;
 
     // print data summary
     if !args.no_summary {
-        crate::summary::print_summary(&io, &output_mode, &args).await?;
+        crate::summary::print_summary(&io, &output_mode, pruning.as_ref(), &args).await?;
     }
 
     // exit early as needed
     exit_early_if_needed(args.dry, args.confirm, !args.no_summary, &output_mode, &io);
 
     // process each input output pair
-    for (input_paths, output_path) in io.into_iter() {
-        process_io(input_paths, output_path, &output_mode, &args)?
+    for ((input_paths, output_path), lookup) in io.into_iter().zip(lookups) {
+        process_io(
+            input_paths,
+            output_path,
+            lookup,
+            &output_mode,
+            &hive_roots,
+            &args,
+        )
+        .await?
     }
 
     Ok(())
 }
 
+/// set the output flag named by --format, so that --format csv behaves like --csv
+fn apply_format(args: &mut DataArgs) -> Result<(), TblCliError> {
+    if args.delimiter.map(|delimiter| !delimiter.is_ascii()) == Some(true) {
+        return Err(TblCliError::Arg(
+            "--delimiter must be an ascii character".to_string(),
+        ));
+    }
+    let format = match &args.format {
+        Some(format) => format.to_lowercase(),
+        None => return Ok(()),
+    };
+    match format.as_str() {
+        "table" | "tsv" | "markdown" | "plain" | "sql" => {}
+        "csv" => args.csv = true,
+        "json" => args.json = true,
+        "jsonl" | "ndjson" => args.jsonl = true,
+        "ipc" | "arrow" => args.ipc = true,
+        "parquet" => args.parquet = true,
+        _ => {
+            return Err(TblCliError::Arg(format!(
+                "unknown output format {}, use table, csv, tsv, json, jsonl, markdown, plain, sql, ipc, or parquet",
+                format
+            )))
+        }
+    }
+    args.format = Some(format);
+    Ok(())
+}
+
 fn decide_output_mode(args: &DataArgs) -> Result<OutputMode, TblCliError> {
     match (
         args.inplace,
@@ -35,64 +123,139 @@ fn decide_output_mode(args: &DataArgs) -> Result<OutputMode, TblCliError> {
         &args.partition,
         args.df,
         args.lf,
+        args.tui,
     ) {
-        (false, None, None, None, false, false) => Ok(OutputMode::PrintToStdout),
-        (true, None, None, None, false, false) => Ok(OutputMode::ModifyInplace),
-        (false, Some(_), None, None, false, false) => Ok(OutputMode::SaveToSingleFile),
-        (false, None, Some(_), None, false, false) => Ok(OutputMode::SaveToDirectory),
-        (false, None, _, Some(_), false, false) => Ok(OutputMode::Partition),
-        (false, None, None, None, true, false) => Ok(OutputMode::InteractiveDf),
-        (false, None, None, None, false, true) => Ok(OutputMode::InteractiveLf),
+        (false, None, None, None, false, false, false) => Ok(OutputMode::PrintToStdout),
+        (true, None, None, None, false, false, false) => Ok(OutputMode::ModifyInplace),
+        (false, Some(_), None, None, false, false, false) => Ok(OutputMode::SaveToSingleFile),
+        (false, None, Some(_), None, false, false, false) => Ok(OutputMode::SaveToDirectory),
+        (false, None, _, Some(_), false, false, false) => Ok(OutputMode::Partition),
+        (false, None, None, None, true, false, false) => Ok(OutputMode::InteractiveDf),
+        (false, None, None, None, false, true, false) => Ok(OutputMode::InteractiveLf),
+        (false, None, None, None, false, false, true) => Ok(OutputMode::Tui),
         _ => Err(TblCliError::Error(
             "can only specify one output mode".to_string(),
         )),
     }
 }
 
+/// reject --set-metadata and --drop-metadata unless every output is a parquet file
+fn validate_metadata_outputs(
+    io: &[(Vec<PathBuf>, Option<PathBuf>)],
+    output_mode: &OutputMode,
+    args: &DataArgs,
+) -> Result<(), TblCliError> {
+    if !output_mode.writes_to_disk() {
+        return Err(TblCliError::Arg(
+            "--set-metadata and --drop-metadata require parquet output files".to_string(),
+        ));
+    }
+    for (_, output_path) in io.iter() {
+        // partition outputs are named after their format
+        let output_path = output_path
+            .as_deref()
+            .unwrap_or(std::path::Path::new("part-0"));
+        let format = crate::output::get_output_file_format(output_path, args)?;
+        if format != OutputFileFormat::Parquet {
+            return Err(TblCliError::Arg(format!(
+                "--set-metadata and --drop-metadata require parquet output, but {} is written as {}",
+                output_path.display(),
+                format.extension()
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::type_complexity)]
-fn gather_inputs_and_outputs(
+async fn gather_inputs_and_outputs(
     output_mode: &OutputMode,
+    hive_roots: &[PathBuf],
     args: &DataArgs,
 ) -> Result<Vec<(Vec<PathBuf>, Option<PathBuf>)>, TblCliError> {
+    // data piped on stdin forms a single input
+    if let Some(paths) = &args.paths {
+        if paths.iter().any(|path| is_stdin_path(path)) {
+            return gather_stdin_input_and_output(paths, output_mode, args);
+        }
+    }
+
     // parse input output pairs
     let mut io = Vec::new();
     match output_mode {
         OutputMode::PrintToStdout
         | OutputMode::Partition
         | OutputMode::InteractiveLf
-        | OutputMode::InteractiveDf => {
-            let input_paths = get_input_paths(&args.paths, args.tree, true)?;
+        | OutputMode::InteractiveDf
+        | OutputMode::Tui => {
+            let input_paths = get_input_paths(&args.paths, args.tree, &args.inputs).await?;
             io.push((input_paths, None))
         }
         OutputMode::SaveToSingleFile => {
-            let input_paths = get_input_paths(&args.paths, args.tree, true)?;
+            let input_paths = get_input_paths(&args.paths, args.tree, &args.inputs).await?;
             io.push((input_paths, args.output_file.clone()))
         }
         OutputMode::ModifyInplace => {
-            let input_paths = get_input_paths(&args.paths, args.tree, true)?;
+            let input_paths = get_input_paths(&args.paths, args.tree, &args.inputs).await?;
             for input_path in input_paths.into_iter() {
+                // a file rewritten in place must keep the format its name says
+                let format = crate::output::get_output_file_format(&input_path, args)?;
+                let file_format =
+                    OutputFileFormat::from_path(&input_path).unwrap_or(OutputFileFormat::Parquet);
+                if format != file_format {
+                    return Err(TblCliError::Arg(format!(
+                        "--inplace cannot write {} as {}, output format must match the file extension",
+                        input_path.display(),
+                        format.extension()
+                    )));
+                }
                 io.push(([input_path.clone()].to_vec(), Some(input_path)))
             }
         }
         OutputMode::SaveToDirectory => {
             if let Some(output_dir) = args.output_dir.clone() {
-                let _ = std::fs::create_dir(output_dir);
+                if !is_remote_path(&output_dir) {
+                    let _ = std::fs::create_dir(output_dir);
+                }
             };
             let output_spec = OutputPathSpec {
-                inputs: args.paths.clone(),
+                inputs: expand_input_patterns(&args.paths, args).await?,
                 output_dir: args.output_dir.clone(),
-                tree: args.tree,
+                tree: args.tree || args.inputs.max_depth.is_some(),
                 file_prefix: args.output_prefix.clone(),
                 file_postfix: args.output_postfix.clone(),
                 sort: true,
+                hive_roots: hive_roots.to_vec(),
             };
             let (input_paths, output_paths) = get_output_paths(output_spec)?;
-            for (input_path, output_path) in input_paths.into_iter().zip(output_paths) {
-                io.push(([input_path].to_vec(), Some(output_path)))
+
+            // keep only files passing input selection options
+            let selected: HashSet<PathBuf> = get_input_paths(&args.paths, args.tree, &args.inputs)
+                .await?
+                .into_iter()
+                .collect();
+            for (input_path, mut output_path) in input_paths.into_iter().zip(output_paths) {
+                // name outputs after the format they are written in
+                let format = crate::output::get_output_file_format(&output_path, args)?;
+                if OutputFileFormat::from_path(&output_path) != Some(format) {
+                    output_path.set_extension(format.extension());
+                }
+                if selected.contains(&input_path) {
+                    io.push(([input_path].to_vec(), Some(output_path)))
+                }
             }
         }
     };
 
+    // skip files whose hive partitions are ruled out by filters
+    let filters: Vec<ColumnFilter> = args
+        .filter
+        .iter()
+        .flatten()
+        .filter_map(|filter| parse_column_filter(filter).ok())
+        .collect();
+    let io = prune_io_by_hive_partitions(io, output_mode, hive_roots, &filters);
+
     // filter empty io pairs
     let io = io
         .into_iter()
@@ -102,6 +265,244 @@ fn gather_inputs_and_outputs(
     Ok(io)
 }
 
+/// skip input files whose hive partitions are ruled out by filters
+///
+/// like statistics pruning, only used when inputs are merged into one output,
+/// since skipping a file in per-file modes would leave its output unwritten
+#[allow(clippy::type_complexity)]
+fn prune_io_by_hive_partitions(
+    io: Vec<(Vec<PathBuf>, Option<PathBuf>)>,
+    output_mode: &OutputMode,
+    hive_roots: &[PathBuf],
+    filters: &[ColumnFilter],
+) -> Vec<(Vec<PathBuf>, Option<PathBuf>)> {
+    if !output_mode.merges_inputs() {
+        return io;
+    }
+    io.into_iter()
+        .map(|(inputs, output)| (prune_hive_paths(inputs, hive_roots, filters), output))
+        .collect()
+}
+
+#[allow(clippy::type_complexity)]
+fn gather_stdin_input_and_output(
+    paths: &[PathBuf],
+    output_mode: &OutputMode,
+    args: &DataArgs,
+) -> Result<Vec<(Vec<PathBuf>, Option<PathBuf>)>, TblCliError> {
+    if paths.len() > 1 {
+        return Err(TblCliError::Arg(
+            "stdin input - cannot be combined with other input paths".to_string(),
+        ));
+    }
+    if args.inputs.files_from.as_deref().map(is_stdin_path) == Some(true) {
+        return Err(TblCliError::Arg(
+            "cannot read both data and --files-from from stdin".to_string(),
+        ));
+    }
+    if get_sample_size(args)?.is_some() {
+        return Err(TblCliError::Arg(
+            "sampling is not supported for stdin input".to_string(),
+        ));
+    }
+    match output_mode {
+        OutputMode::PrintToStdout | OutputMode::Partition | OutputMode::Tui => {
+            Ok(vec![(paths.to_vec(), None)])
+        }
+        OutputMode::SaveToSingleFile => Ok(vec![(paths.to_vec(), args.output_file.clone())]),
+        _ => Err(TblCliError::Arg(
+            "stdin input can only be printed, saved to --output-file, or partitioned".to_string(),
+        )),
+    }
+}
+
+/// number of selected rows up to which only the selected row groups and pages are read
+const POINT_LOOKUP_MAX_ROWS: u64 = 1_000_000;
+
+/// skip input files that cannot contain rows passing `--filter`
+///
+/// only used when inputs are merged into one output, since skipping a file
+/// in per-file modes would leave its output unwritten. sampling also disables
+/// pruning because rows are sampled before filtering.
+///
+/// when statistics, bloom filters, and page indexes leave few enough rows,
+/// the row selection of each input is also returned so that only those rows
+/// are read
+#[allow(clippy::type_complexity)]
+async fn prune_io_by_statistics(
+    io: Vec<(Vec<PathBuf>, Option<PathBuf>)>,
+    output_mode: &OutputMode,
+    args: &DataArgs,
+) -> Result<
+    (
+        Vec<(Vec<PathBuf>, Option<PathBuf>)>,
+        Option<PruneSummary>,
+        Vec<Option<Vec<ParquetRowSelection>>>,
+    ),
+    TblCliError,
+> {
+    let stdin = io
+        .iter()
+        .flat_map(|(inputs, _)| inputs)
+        .any(|path| is_stdin_path(path));
+    if !output_mode.merges_inputs()
+        || stdin
+        || args.filter.is_none()
+        || get_sample_size(args)?.is_some()
+    {
+        let lookups = vec![None; io.len()];
+        return Ok((io, None, lookups));
+    }
+
+    // filters on added columns cannot be checked against file statistics
+    let new_columns: Vec<&str> = args
+        .with_columns
+        .iter()
+        .flatten()
+        .map(|spec| spec.split([':', '=']).next().unwrap_or_default())
+        .collect();
+    let filters: Vec<ColumnFilter> = args
+        .filter
+        .iter()
+        .flatten()
+        .filter_map(|filter| parse_column_filter(filter).ok())
+        .filter(|filter| !new_columns.contains(&filter.column.as_str()))
+        .collect();
+
+    // files listed in --index are first ruled out by their manifest entries
+    let entries = match &args.inputs.index {
+        Some(index_path) if !filters.is_empty() => read_parquet_index(index_path)?,
+        _ => Vec::new(),
+    };
+
+    let mut total = PruneSummary::default();
+    let mut pruned = Vec::new();
+    let mut lookups = Vec::new();
+    for (inputs, output) in io.into_iter() {
+        let inputs = match entries.is_empty() {
+            true => inputs,
+            false => {
+                let (inputs, summary) = prune_paths_by_index(inputs, &entries, &filters);
+                total.add(&summary);
+                inputs
+            }
+        };
+        if filters.is_empty() || inputs.is_empty() {
+            let (inputs, summary) = prune_paths_by_statistics(inputs, &filters).await?;
+            total.add(&summary);
+            pruned.push((inputs, output));
+            lookups.push(None);
+            continue;
+        }
+        let (selections, summary) =
+            select_parquets_rows(&inputs, &filters, DEFAULT_MAX_CONCURRENT).await?;
+        total.add(&summary);
+        let inputs = selections.iter().map(|s| s.path.clone()).collect();
+        let n_selected_rows: u64 = selections.iter().map(|s| s.n_selected_rows).sum();
+        let point_lookup = n_selected_rows <= POINT_LOOKUP_MAX_ROWS;
+        pruned.push((inputs, output));
+        lookups.push(point_lookup.then_some(selections));
+    }
+    Ok((pruned, Some(total), lookups))
+}
+
+/// columns that point lookups need to read, none if all columns are needed
+///
+/// only --columns and the columns of --filter are read when no other
+/// transformation before column selection refers to columns
+fn get_lookup_columns(args: &DataArgs) -> Option<Vec<String>> {
+    let columns = args.columns.as_ref()?;
+    if args.with_columns.is_some()
+        || args.drop.is_some()
+        || args.cast.is_some()
+        || args.set.is_some()
+        || args.nullify.is_some()
+        || args.replace.is_some()
+        || args.unique.is_some()
+    {
+        return None;
+    }
+    let mut columns = columns.clone();
+    for filter in args.filter.iter().flatten() {
+        let column = parse_column_filter(filter).ok()?.column;
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+    Some(columns)
+}
+
+/// predicate of --filter applied to each file of a point lookup as it is read,
+/// none if filters refer to columns added by --with-columns
+fn get_lookup_predicate(
+    selections: &[ParquetRowSelection],
+    hive_roots: &[PathBuf],
+    args: &DataArgs,
+) -> Result<Option<Expr>, TblCliError> {
+    if args.with_columns.is_some() || selections.is_empty() {
+        return Ok(None);
+    }
+    let paths: Vec<PathBuf> = selections.iter().map(|s| s.path.clone()).collect();
+    let schema = tbl_core::parquet::create_lazyframe(&paths, hive_roots)?.schema()?;
+    let mut predicate: Option<Expr> = None;
+    for filter in args.filter.iter().flatten() {
+        let expr = match crate::transform::get_filter_expr(filter, &schema) {
+            Ok(expr) => expr,
+            Err(_) => return Ok(None),
+        };
+        predicate = Some(match predicate {
+            Some(predicate) => predicate.and(expr),
+            None => expr,
+        });
+    }
+    Ok(predicate)
+}
+
+/// drop hive partition columns that were added from directory names of inputs,
+/// keeping columns of the same name that the input files store themselves
+async fn drop_hive_columns(
+    lf: LazyFrame,
+    input_paths: &[PathBuf],
+    hive_roots: &[PathBuf],
+) -> Result<LazyFrame, TblCliError> {
+    let hive_columns = get_added_hive_columns(input_paths, hive_roots).await?;
+    if hive_columns.is_empty() {
+        return Ok(lf);
+    }
+    let schema = lf.clone().schema()?;
+    let present: Vec<String> = hive_columns
+        .into_iter()
+        .filter(|column| schema.contains(column))
+        .collect();
+    Ok(lf.drop(present))
+}
+
+/// replace remote prefixes and glob patterns with the files they contain,
+/// and append paths listed by --files-from
+async fn expand_input_patterns(
+    inputs: &Option<Vec<PathBuf>>,
+    args: &DataArgs,
+) -> Result<Option<Vec<PathBuf>>, TblCliError> {
+    let listed_paths = &args.inputs.listed_paths;
+    let inputs = match (inputs, listed_paths) {
+        (Some(inputs), _) => inputs,
+        (None, Some(listed_paths)) => return Ok(Some(listed_paths.clone())),
+        (None, None) => return Ok(None),
+    };
+    let mut expanded = Vec::new();
+    for input in inputs.iter() {
+        if is_remote_path(input) || is_glob_pattern(input) {
+            let inputs = Some(vec![input.clone()]);
+            let spec = get_input_spec(&inputs, args.tree, &args.inputs)?.listed_paths(None);
+            expanded.extend(select_input_paths(&spec).await?);
+        } else {
+            expanded.push(input.clone());
+        }
+    }
+    expanded.extend(listed_paths.iter().flatten().cloned());
+    Ok(Some(expanded))
+}
+
 fn exit_early_if_needed(
     dry: bool,
     confirm: bool,
@@ -146,18 +547,134 @@ fn exit_early_if_needed(
     }
 }
 
-fn process_io(
+async fn process_io(
     input_paths: Vec<PathBuf>,
     output_path: Option<PathBuf>,
+    lookup: Option<Vec<ParquetRowSelection>>,
     output_mode: &OutputMode,
+    hive_roots: &[PathBuf],
     args: &DataArgs,
 ) -> Result<(), TblCliError> {
-    // create lazy frame
-    let lf = tbl_core::parquet::create_lazyframe(&input_paths)?;
+    // stream stdout output in row groups when rows are transformed independently
+    let stdin = input_paths.iter().any(|path| is_stdin_path(path));
+    if matches!(output_mode, OutputMode::PrintToStdout)
+        && !stdin
+        && lookup.is_none()
+        && crate::output::can_stream_files_to_stdout(args)
+    {
+        return crate::output::stream_files_to_stdout(&input_paths, hive_roots, args).await;
+    }
+
+    // create lazy frame, sampling rows before other transformations
+    let lf = match get_sample_size(args)? {
+        _ if stdin => read_stdin_lazyframe(args.input_format.as_deref())?,
+        None if lookup.is_some() => {
+            let selections = lookup.unwrap_or_default();
+            let columns = get_lookup_columns(args);
+            let predicate = get_lookup_predicate(&selections, hive_roots, args)?;
+            tbl_core::parquet::create_selected_lazyframe(
+                &selections,
+                hive_roots,
+                columns.as_deref(),
+                predicate,
+            )
+            .await?
+        }
+        Some(sample) => {
+            let seed = args.seed.unwrap_or_default();
+            match args.sample_by.as_deref() {
+                Some(column) => {
+                    tbl_core::parquet::create_stratified_lazyframe(
+                        &input_paths,
+                        hive_roots,
+                        column,
+                        sample,
+                        seed,
+                    )
+                    .await?
+                }
+                None => {
+                    tbl_core::parquet::create_sampled_lazyframe(
+                        &input_paths,
+                        hive_roots,
+                        sample,
+                        seed,
+                    )
+                    .await?
+                }
+            }
+        }
+        None => tbl_core::parquet::create_lazyframe(&input_paths, hive_roots)?,
+    };
 
     // transform into output frames
     let lf = crate::transform::apply_transformations(lf, args)?;
 
+    // hive partition columns are stored in directory names of per-file outputs
+    let lf = match output_mode {
+        OutputMode::SaveToDirectory | OutputMode::ModifyInplace => {
+            drop_hive_columns(lf, &input_paths, hive_roots).await?
+        }
+        _ => lf,
+    };
+
     // output data
-    crate::output::output_lazyframe(lf, input_paths, output_path, output_mode, args)
+    crate::output::output_lazyframe(lf, input_paths, output_path, output_mode, args).await
+}
+
+fn get_sample_size(args: &DataArgs) -> Result<Option<SampleSize>, TblCliError> {
+    match (args.sample, args.sample_frac) {
+        (Some(_), Some(_)) => Err(TblCliError::Arg(
+            "cannot use both --sample and --sample-frac".to_string(),
+        )),
+        (Some(n), None) => Ok(Some(SampleSize::Rows(n))),
+        (None, Some(fraction)) if (0.0..=1.0).contains(&fraction) => {
+            Ok(Some(SampleSize::Fraction(fraction)))
+        }
+        (None, Some(_)) => Err(TblCliError::Arg(
+            "--sample-frac must be between 0 and 1".to_string(),
+        )),
+        (None, None) if args.sample_by.is_some() => Err(TblCliError::Arg(
+            "--sample-by requires --sample or --sample-frac".to_string(),
+        )),
+        (None, None) => Ok(None),
+    }
+}
+
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_io_by_hive_partitions() {
+        let roots = vec![PathBuf::from("/data")];
+        let root = &roots[0];
+        let paths = vec![
+            root.join("year=2023/part-0.parquet"),
+            root.join("year=2024/part-0.parquet"),
+        ];
+        let filters = vec![parse_column_filter("year>=2024").unwrap()];
+
+        // merged outputs only read the partitions that can match
+        let io = vec![(paths.clone(), None)];
+        let pruned = prune_io_by_hive_partitions(io, &OutputMode::PrintToStdout, &roots, &filters);
+        assert_eq!(pruned, vec![(vec![paths[1].clone()], None)]);
+
+        // per-file outputs are all rewritten, including ruled out partitions
+        let io: Vec<(Vec<PathBuf>, Option<PathBuf>)> = paths
+            .iter()
+            .map(|path| (vec![path.clone()], Some(path.clone())))
+            .collect();
+        for output_mode in [OutputMode::ModifyInplace, OutputMode::SaveToDirectory] {
+            let pruned = prune_io_by_hive_partitions(io.clone(), &output_mode, &roots, &filters);
+            assert_eq!(pruned, io);
+        }
+    }
 }
@@ -0,0 +1,101 @@
+use crate::styles::FontStyle;
+use crate::{FindArgs, TblCliError};
+use color_print::cstr;
+use polars::prelude::*;
+use tbl_core::filters::parse_column_filter;
+use tbl_core::formats::format_with_commas;
+use tbl_core::parquet::{PruneSummary, FIND_PATH_COLUMN, FIND_ROW_OFFSET_COLUMN};
+use toolstr::Colorize;
+
+pub(crate) async fn find_command(args: FindArgs) -> Result<(), TblCliError> {
+    // get paths
+    let paths = crate::inputs::get_input_paths(&args.paths, args.tree, &args.inputs).await?;
+    if paths.is_empty() {
+        println!("[no tabular paths]");
+        return Ok(());
+    }
+
+    // parse query
+    let filter = parse_column_filter(&args.query)?;
+    let schema = tbl_core::parquet::get_parquet_schema(&paths[0]).await?;
+    let predicate = crate::transform::get_filter_expr(&args.query, &schema)?;
+
+    // find rows
+    let (df, summary) =
+        tbl_core::parquet::find_parquet_rows(&paths, &[filter], predicate, args.max_concurrent)
+            .await?;
+    let df = match args.columns.as_ref() {
+        Some(columns) if df.height() > 0 => {
+            let mut selected = vec![
+                FIND_PATH_COLUMN.to_string(),
+                FIND_ROW_OFFSET_COLUMN.to_string(),
+            ];
+            selected.extend(columns.iter().cloned());
+            df.select(selected)?
+        }
+        _ => df,
+    };
+
+    // print results
+    if !args.json {
+        print_find_summary(&df, &summary)?;
+    }
+    if df.height() == 0 {
+        if !args.json {
+            println!("[no matching rows]");
+        }
+        return Ok(());
+    }
+    let n_show = match args.n.as_deref() {
+        Some("all") => df.height(),
+        Some(n) => n.parse::<usize>()?,
+        None => 20,
+    };
+    let shown = crate::output::binary_to_hex(&mut df.head(Some(n_show)))?;
+    if args.json {
+        crate::output::print_dataframe_as_json(&shown, n_show, false)?;
+        return Ok(());
+    }
+    println!();
+    println!("{}", shown);
+    if df.height() > n_show {
+        println!(
+            "{} rows omitted, use {} to show all rows",
+            (df.height() - n_show)
+                .to_string()
+                .colorize_constant()
+                .bold(),
+            cstr!("<white><bold>-n all</bold></white>")
+        );
+    }
+
+    Ok(())
+}
+
+fn print_find_summary(df: &DataFrame, summary: &PruneSummary) -> Result<(), TblCliError> {
+    let n_matching_files = match df.height() {
+        0 => 0,
+        _ => df.column(FIND_PATH_COLUMN)?.n_unique()?,
+    };
+    println!(
+        "found {} rows in {} of {} files",
+        format_with_commas(df.height() as u64).green().bold(),
+        format_with_commas(n_matching_files as u64).green().bold(),
+        format_with_commas(summary.n_files).green().bold(),
+    );
+    println!(
+        "{}",
+        format!(
+            "statistics and bloom filters ruled out {} of {} files, {} of {} row groups, and {} of {} rows",
+            format_with_commas(summary.n_skipped_files),
+            format_with_commas(summary.n_files),
+            format_with_commas(summary.n_skipped_row_groups),
+            format_with_commas(summary.n_row_groups),
+            format_with_commas(summary.n_skipped_rows),
+            format_with_commas(summary.n_rows),
+        )
+        .as_str()
+        .colorize_comment()
+    );
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use crate::{IndexArgs, TblCliError};
+use std::path::PathBuf;
+use tbl_core::formats::{format_bytes, format_with_commas};
+use toolstr::Colorize;
+
+pub(crate) async fn index_command(args: IndexArgs) -> Result<(), TblCliError> {
+    // get paths, skipping any previous manifest at the output path
+    let paths = crate::inputs::get_input_paths(&args.paths, args.tree, &args.inputs).await?;
+    let output = std::path::absolute(&args.output)?;
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|path| std::path::absolute(path).ok().as_ref() != Some(&output))
+        .collect();
+    if paths.is_empty() {
+        println!("[no tabular paths]");
+        return Ok(());
+    }
+
+    // write manifest
+    let entries = tbl_core::parquet::write_parquet_index(&paths, &args.output).await?;
+
+    // print summary
+    let n_rows: u64 = entries.iter().map(|entry| entry.n_rows).sum();
+    let n_bytes: u64 = entries.iter().map(|entry| entry.n_bytes).sum();
+    println!(
+        "indexed {} files with {} rows in {} to {}",
+        format_with_commas(entries.len() as u64).green().bold(),
+        format_with_commas(n_rows).green().bold(),
+        format_bytes(n_bytes).green().bold(),
+        args.output.to_string_lossy().purple(),
+    );
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use crate::styles::FontStyle;
+use crate::{InspectArgs, TblCliError};
+use tbl_core::formats::{format_bytes, format_with_commas};
+use tbl_core::parquet::{ColumnChunkInspection, RowGroupInspection};
+use toolstr::Colorize;
+
+pub(crate) async fn inspect_command(args: InspectArgs) -> Result<(), TblCliError> {
+    let inspection = tbl_core::parquet::inspect_parquet_file(&args.path).await?;
+
+    // print file summary
+    println!(
+        "{} rows in {} row groups",
+        format_with_commas(inspection.n_rows).green().bold(),
+        format_with_commas(inspection.row_groups.len() as u64)
+            .green()
+            .bold(),
+    );
+    if let Some(created_by) = &inspection.created_by {
+        println!("created by {}", created_by.as_str().colorize_comment());
+    }
+    if !inspection.key_value_metadata.is_empty() {
+        println!();
+        println!("{}", "Key-value metadata".colorize_title());
+        for (key, value) in inspection.key_value_metadata.iter() {
+            let value = match value {
+                Some(value) if args.full_metadata => value.clone(),
+                Some(value) => truncate_metadata(value),
+                None => "null".to_string(),
+            };
+            println!(
+                "{}: {}",
+                key.as_str().colorize_variable(),
+                value.colorize_string()
+            );
+        }
+    }
+
+    // print row groups
+    if let Some(row_group) = args.row_group {
+        if row_group >= inspection.row_groups.len() {
+            return Err(TblCliError::Arg(format!(
+                "file only has {} row groups",
+                inspection.row_groups.len()
+            )));
+        }
+    }
+    for (rg, row_group) in inspection.row_groups.iter().enumerate() {
+        if args.row_group.map(|n| n != rg).unwrap_or(false) {
+            continue;
+        }
+        println!();
+        println!();
+        print_row_group(rg, row_group, args.columns.as_deref())?;
+    }
+
+    Ok(())
+}
+
+fn print_row_group(
+    rg: usize,
+    row_group: &RowGroupInspection,
+    columns: Option<&[String]>,
+) -> Result<(), TblCliError> {
+    println!(
+        "{} {}{} {} rows, {} on disk, {} uncompressed",
+        "Row group".colorize_title(),
+        rg.to_string().green().bold(),
+        ":".colorize_title(),
+        format_with_commas(row_group.n_rows).green().bold(),
+        format_bytes(row_group.n_bytes_compressed).green().bold(),
+        format_bytes(row_group.n_bytes_uncompressed).green().bold(),
+    );
+    println!();
+
+    let chunks: Vec<&ColumnChunkInspection> = row_group
+        .columns
+        .iter()
+        .filter(|chunk| columns.map(|c| c.contains(&chunk.name)).unwrap_or(true))
+        .collect();
+    let values = |f: &dyn Fn(&ColumnChunkInspection) -> String| -> Vec<String> {
+        chunks.iter().map(|chunk| f(chunk)).collect()
+    };
+    let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+
+    // build table
+    let mut table = toolstr::Table::new();
+    table.add_column("column", values(&|c| c.name.clone()))?;
+    table.add_column("type", values(&|c| c.physical_type.clone()))?;
+    table.add_column("encodings", values(&|c| c.encodings.join(",")))?;
+    table.add_column("codec", values(&|c| c.compression.clone()))?;
+    table.add_column("disk size", values(&|c| format_bytes(c.n_bytes_compressed)))?;
+    table.add_column(
+        "full size",
+        values(&|c| format_bytes(c.n_bytes_uncompressed)),
+    )?;
+    table.add_column(
+        "pages",
+        values(&|c| optional(c.n_pages.map(|n| n.to_string()))),
+    )?;
+    table.add_column(
+        "dict size",
+        values(&|c| optional(c.dictionary_page_size.map(format_bytes))),
+    )?;
+    table.add_column("stats", values(&|c| yes_no(c.has_statistics)))?;
+    table.add_column(
+        "min",
+        values(&|c| optional(c.statistics.min.as_ref().map(|v| truncate(&v.to_string())))),
+    )?;
+    table.add_column(
+        "max",
+        values(&|c| optional(c.statistics.max.as_ref().map(|v| truncate(&v.to_string())))),
+    )?;
+    table.add_column(
+        "nulls",
+        values(&|c| optional(c.statistics.null_count.map(format_with_commas))),
+    )?;
+    table.add_column("bloom", values(&|c| yes_no(c.has_bloom_filter)))?;
+    table.add_column(
+        "page index",
+        values(&|c| yes_no(c.has_column_index && c.has_offset_index)),
+    )?;
+
+    // create format
+    let mut format = toolstr::TableFormat {
+        label_font_style: Some("".colorize_title().into()),
+        border_font_style: Some("".colorize_comment().into()),
+        ..Default::default()
+    };
+    let styles = [
+        ("column", "".colorize_function()),
+        ("type", "".colorize_variable()),
+        ("encodings", "".colorize_variable()),
+        ("codec", "".colorize_variable()),
+        ("disk size", "".colorize_constant()),
+        ("full size", "".colorize_constant()),
+        ("pages", "".colorize_constant()),
+        ("dict size", "".colorize_constant()),
+        ("stats", "".colorize_comment()),
+        ("min", "".colorize_string()),
+        ("max", "".colorize_string()),
+        ("nulls", "".colorize_constant()),
+        ("bloom", "".colorize_comment()),
+        ("page index", "".colorize_comment()),
+    ];
+    for (name, style) in styles.into_iter() {
+        let mut column = toolstr::ColumnFormatShorthand::default().name(name);
+        column.font_style = Some(style.into());
+        format.add_column(column);
+    }
+
+    // print table
+    format.print(table)?;
+
+    Ok(())
+}
+
+/// shorten long min and max values so that tables stay readable
+fn truncate(value: &str) -> String {
+    let max_chars = 24;
+    if value.chars().count() <= max_chars {
+        value.to_string()
+    } else {
+        let mut truncated: String = value.chars().take(max_chars - 3).collect();
+        truncated.push_str("...");
+        truncated
+    }
+}
+
+/// shorten long metadata values such as encoded arrow schemas
+fn truncate_metadata(value: &str) -> String {
+    let max_chars = 80;
+    if value.chars().count() <= max_chars {
+        value.to_string()
+    } else {
+        let truncated: String = value.chars().take(max_chars).collect();
+        format!(
+            "{}... ({} bytes)",
+            truncated,
+            format_with_commas(value.len() as u64)
+        )
+    }
+}
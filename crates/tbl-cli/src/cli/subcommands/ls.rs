@@ -3,7 +3,8 @@ use toolstr::Colorize;
 
 pub(crate) async fn ls_command(ls_args: LsArgs) -> Result<(), TblCliError> {
     // get paths
-    let paths = tbl_core::filesystem::get_input_paths(&ls_args.paths, ls_args.tree, true)?;
+    let paths =
+        crate::inputs::get_input_paths(&ls_args.paths, ls_args.tree, &ls_args.inputs).await?;
 
     if paths.is_empty() {
         println!("[no tabular paths]");
@@ -14,7 +15,7 @@ pub(crate) async fn ls_command(ls_args: LsArgs) -> Result<(), TblCliError> {
     print_file_names(&paths, ls_args.n, ls_args.absolute)?;
 
     // print stats
-    print_stats(&paths).await?;
+    print_stats(&paths, ls_args.cache).await?;
 
     Ok(())
 }
@@ -70,18 +71,19 @@ fn print_file_names(
     Ok(())
 }
 
-async fn print_stats(paths: &[std::path::PathBuf]) -> Result<(), TblCliError> {
+async fn print_stats(paths: &[std::path::PathBuf], cache: bool) -> Result<(), TblCliError> {
     // get total file size
-    let mut total_size: u64 = 0;
-    for path in paths.iter() {
-        let metadata = std::fs::metadata(path)?;
-        total_size += metadata.len();
-    }
-
-    // get row counts
     let path_refs: Vec<&std::path::Path> =
         paths.iter().map(|path_buf| path_buf.as_path()).collect();
-    let row_counts = tbl_core::parquet::get_parquet_row_counts(&path_refs).await?;
+    let total_size = tbl_core::filesystem::get_total_bytes_of_files(&path_refs).await?;
+
+    // get row counts
+    let row_counts = match crate::inputs::get_cache_dir(cache)? {
+        Some(cache_dir) => {
+            tbl_core::parquet::get_parquet_row_counts_cached(paths, &cache_dir).await?
+        }
+        None => tbl_core::parquet::get_parquet_row_counts(&path_refs).await?,
+    };
 
     // print total summary
     println!(
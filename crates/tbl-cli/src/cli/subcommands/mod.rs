@@ -9,3 +9,18 @@ pub(crate) use schema::*;
 
 mod schemas;
 pub(crate) use schemas::*;
+
+mod check;
+pub(crate) use check::*;
+
+mod coverage;
+pub(crate) use coverage::*;
+
+mod index;
+pub(crate) use index::*;
+
+mod inspect;
+pub(crate) use inspect::*;
+
+mod find;
+pub(crate) use find::*;
@@ -10,8 +10,13 @@ use toolstr::Colorize;
 
 pub(crate) async fn schema_command(args: SchemaArgs) -> Result<(), TblCliError> {
     // get schemas
-    let paths = tbl_core::filesystem::get_input_paths(&args.paths, args.tree, true)?;
-    let summaries = tbl_core::parquet::get_parquet_summaries(&paths).await?;
+    let paths = crate::inputs::get_input_paths(&args.paths, args.tree, &args.inputs).await?;
+    let summaries = match crate::inputs::get_cache_dir(args.cache)? {
+        Some(cache_dir) => {
+            tbl_core::parquet::get_parquet_summaries_cached(&paths, &cache_dir).await?
+        }
+        None => tbl_core::parquet::get_parquet_summaries(&paths).await?,
+    };
     let ref_summaries: Vec<&tbl_core::parquet::TabularSummary> = summaries.iter().collect();
     let by_schema = summarize_by_schema(ref_summaries.as_slice())?;
 
@@ -108,11 +113,9 @@ fn top_n_schemas(
 ) -> Vec<TabularSummary> {
     let mut summaries: Vec<_> = schema_summaries.values().cloned().collect();
     match sort_by {
-        SortSchemasBy::Rows => summaries.sort_by(|a, b| b.n_rows.cmp(&a.n_rows)),
-        SortSchemasBy::Files => summaries.sort_by(|a, b| b.n_files.cmp(&a.n_files)),
-        SortSchemasBy::Bytes => {
-            summaries.sort_by(|a, b| b.n_bytes_compressed.cmp(&a.n_bytes_compressed))
-        }
+        SortSchemasBy::Rows => summaries.sort_by_key(|s| std::cmp::Reverse(s.n_rows)),
+        SortSchemasBy::Files => summaries.sort_by_key(|s| std::cmp::Reverse(s.n_files)),
+        SortSchemasBy::Bytes => summaries.sort_by_key(|s| std::cmp::Reverse(s.n_bytes_compressed)),
     }
     summaries.into_iter().take(n_to_show).collect()
 }
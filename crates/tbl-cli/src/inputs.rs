@@ -0,0 +1,247 @@
+use crate::{InputArgs, TblCliError};
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tbl_core::filesystem::InputPathSpec;
+use tbl_core::filesystem::{filter_paths_by_file_stats, read_path_list, select_input_paths};
+use tbl_core::parquet::{
+    get_parquet_schema, index_entry_matches_selection, read_index_paths, read_parquet_index,
+    select_files_by_metadata, FileIndexEntry, FileSelection,
+};
+
+/// read paths of --files-from and --index, reusing them if already read
+pub(crate) fn get_listed_paths(args: &InputArgs) -> Result<Option<Vec<PathBuf>>, TblCliError> {
+    if let Some(listed_paths) = &args.listed_paths {
+        return Ok(Some(listed_paths.clone()));
+    }
+    let mut listed_paths = match &args.files_from {
+        Some(files_from) => Some(read_path_list(files_from)?),
+        None => None,
+    };
+    if let Some(index) = &args.index {
+        listed_paths
+            .get_or_insert_with(Vec::new)
+            .extend(read_index_paths(index)?);
+    }
+    Ok(listed_paths)
+}
+
+/// build input path spec from cli arguments
+pub(crate) fn get_input_spec(
+    paths: &Option<Vec<PathBuf>>,
+    tree: bool,
+    args: &InputArgs,
+) -> Result<InputPathSpec, TblCliError> {
+    Ok(InputPathSpec::new()
+        .inputs(paths.clone())
+        .tree(tree)
+        .sort(true)
+        .exclude(args.exclude.clone().unwrap_or_default())
+        .max_depth(args.max_depth)
+        .follow_symlinks(!args.no_follow_symlinks)
+        .hidden(!args.no_hidden)
+        .listed_paths(get_listed_paths(args)?))
+}
+
+/// build metadata selection predicates from cli arguments
+pub(crate) async fn get_file_selection(args: &InputArgs) -> Result<FileSelection, TblCliError> {
+    let modified_since = match &args.modified_since {
+        Some(time) => Some(parse_modified_since(time)?),
+        None => None,
+    };
+    let schema = match &args.schema_matches {
+        Some(path) => Some(get_parquet_schema(path).await?),
+        None => None,
+    };
+    Ok(FileSelection {
+        min_rows: args.min_rows,
+        max_rows: args.max_rows,
+        min_bytes: args.min_bytes,
+        modified_since,
+        has_columns: args.has_column.clone().unwrap_or_default(),
+        missing_columns: args.missing_column.clone().unwrap_or_default(),
+        schema,
+    })
+}
+
+/// get input file paths selected by cli arguments
+pub(crate) async fn get_input_paths(
+    paths: &Option<Vec<PathBuf>>,
+    tree: bool,
+    args: &InputArgs,
+) -> Result<Vec<PathBuf>, TblCliError> {
+    let spec = get_input_spec(paths, tree, args)?;
+    let paths = select_input_paths(&spec).await?;
+    let selection = get_file_selection(args).await?;
+    if selection.is_empty() {
+        return Ok(paths);
+    }
+    let index_path = match &args.index {
+        Some(index_path) => index_path,
+        None => return Ok(select_files_by_metadata(paths, &selection).await?),
+    };
+
+    // files listed in the manifest are selected by their entries instead of their footers
+    let entries = read_parquet_index(index_path)?;
+    let entries: HashMap<&Path, &FileIndexEntry> = entries
+        .iter()
+        .map(|entry| (entry.path.as_path(), entry))
+        .collect();
+    let (indexed, unindexed): (Vec<PathBuf>, Vec<PathBuf>) = paths
+        .iter()
+        .cloned()
+        .partition(|path| entries.contains_key(path.as_path()));
+    let indexed = indexed
+        .into_iter()
+        .filter(|path| index_entry_matches_selection(entries[path.as_path()], &selection))
+        .collect();
+    let mut selected: HashSet<PathBuf> = select_files_by_metadata(unindexed, &selection)
+        .await?
+        .into_iter()
+        .collect();
+    selected.extend(filter_paths_by_file_stats(indexed, None, selection.modified_since).await?);
+    Ok(paths
+        .into_iter()
+        .filter(|path| selected.contains(path))
+        .collect())
+}
+
+/// get directory of summary cache if caching is enabled
+pub(crate) fn get_cache_dir(cache: bool) -> Result<Option<PathBuf>, TblCliError> {
+    if !cache {
+        return Ok(None);
+    }
+    match tbl_core::parquet::get_default_cache_dir() {
+        Some(cache_dir) => Ok(Some(cache_dir)),
+        None => Err(TblCliError::Arg(format!(
+            "could not locate cache directory, set {}",
+            tbl_core::parquet::CACHE_DIR_ENV_VAR
+        ))),
+    }
+}
+
+/// parse a date, datetime, or age such as `7d` into a point in time
+fn parse_modified_since(time: &str) -> Result<SystemTime, TblCliError> {
+    let invalid = || TblCliError::Error(format!("invalid --modified-since time: {}", time));
+
+    // relative ages
+    let units = [
+        ('s', 1),
+        ('m', 60),
+        ('h', 3600),
+        ('d', 86400),
+        ('w', 604800),
+    ];
+    for (suffix, seconds) in units.iter() {
+        if let Some(count) = time.strip_suffix(*suffix) {
+            if let Ok(count) = count.parse::<u64>() {
+                return count
+                    .checked_mul(*seconds)
+                    .and_then(|age| SystemTime::now().checked_sub(Duration::from_secs(age)))
+                    .ok_or_else(invalid);
+            }
+        }
+    }
+
+    // absolute times, interpreted as utc
+    let datetime = if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(time) {
+        datetime.naive_utc()
+    } else if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S") {
+        datetime
+    } else if let Ok(date) = chrono::NaiveDate::parse_from_str(time, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?
+    } else {
+        return Err(invalid());
+    };
+    Ok(datetime.and_utc().into())
+}
+
+/// input path that reads tabular data from stdin
+pub(crate) const STDIN_PATH: &str = "-";
+
+/// return true if path refers to stdin
+pub(crate) fn is_stdin_path(path: &Path) -> bool {
+    path == Path::new(STDIN_PATH)
+}
+
+/// read tabular data piped on stdin
+///
+/// format is one of csv, json, jsonl, parquet, or ipc, and is detected from
+/// the data if not given
+pub(crate) fn read_stdin_lazyframe(format: Option<&str>) -> Result<LazyFrame, TblCliError> {
+    let mut bytes = Vec::new();
+    std::io::stdin().read_to_end(&mut bytes)?;
+    let format = match format {
+        Some(format) => format.to_lowercase(),
+        None => detect_input_format(&bytes).to_string(),
+    };
+    let cursor = std::io::Cursor::new(bytes);
+    let df = match format.as_str() {
+        "csv" => CsvReadOptions::default()
+            .with_has_header(true)
+            .into_reader_with_file_handle(cursor)
+            .finish()?,
+        "json" => JsonReader::new(cursor)
+            .with_json_format(JsonFormat::Json)
+            .finish()?,
+        "jsonl" | "ndjson" => JsonReader::new(cursor)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish()?,
+        "parquet" => ParquetReader::new(cursor).finish()?,
+        "ipc" | "arrow" if is_ipc_file(cursor.get_ref()) => IpcReader::new(cursor).finish()?,
+        "ipc" | "arrow" => IpcStreamReader::new(cursor).finish()?,
+        _ => {
+            return Err(TblCliError::Arg(format!(
+                "unknown input format {}, use csv, json, jsonl, parquet, or ipc",
+                format
+            )))
+        }
+    };
+    Ok(df.lazy())
+}
+
+fn is_ipc_file(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"ARROW1")
+}
+
+/// guess format of tabular data from its leading bytes
+fn detect_input_format(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"PAR1") {
+        return "parquet";
+    }
+    if is_ipc_file(bytes) || bytes.starts_with(&[0xff, 0xff, 0xff, 0xff]) {
+        return "ipc";
+    }
+    match bytes.iter().find(|byte| !byte.is_ascii_whitespace()) {
+        Some(b'{') => "jsonl",
+        Some(b'[') => "json",
+        _ => "csv",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_input_format() {
+        // parquet magic
+        assert_eq!(detect_input_format(b"PAR1\x15\x04\x15"), "parquet");
+
+        // arrow ipc file magic and ipc stream continuation marker
+        assert_eq!(detect_input_format(b"ARROW1\0\0\xff\xff"), "ipc");
+        assert_eq!(detect_input_format(&[0xff, 0xff, 0xff, 0xff, 0x10]), "ipc");
+
+        // json lines and json arrays, after leading whitespace
+        assert_eq!(detect_input_format(b"{\"a\": 1}\n{\"a\": 2}\n"), "jsonl");
+        assert_eq!(detect_input_format(b"\n  {\"a\": 1}\n"), "jsonl");
+        assert_eq!(detect_input_format(b"[{\"a\": 1}]"), "json");
+
+        // anything else falls back to csv
+        assert_eq!(detect_input_format(b"a,b\n1,2\n"), "csv");
+        assert_eq!(detect_input_format(b""), "csv");
+        assert_eq!(detect_input_format(b"PAR"), "csv");
+    }
+}
@@ -24,6 +24,10 @@ mod transform;
 
 mod output;
 
+mod inputs;
+
+mod tui;
+
 #[tokio::main]
 async fn main() -> Result<(), TblCliError> {
     cli::run_cli().await
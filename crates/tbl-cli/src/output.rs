@@ -1,12 +1,13 @@
-use crate::styles::FontStyle;
-use crate::{DataArgs, OutputMode, TblCliError};
+use crate::{DataArgs, OutputFileFormat, OutputMode, TblCliError};
 use color_print::cstr;
+use polars::export::arrow::io::ipc::write::{StreamWriter, WriteOptions};
 use polars::prelude::*;
-use std::io::stdout;
-use std::path::PathBuf;
-use toolstr::Colorize;
+use std::io::{stdout, BufWriter, IsTerminal, StdoutLock, Write};
+use std::path::{Path, PathBuf};
+use tbl_core::parquet::{ParquetLayout, SortedColumn};
+use tbl_core::writers::FileWriteOptions;
 
-pub(crate) fn output_lazyframe(
+pub(crate) async fn output_lazyframe(
     lf: LazyFrame,
     input_paths: Vec<PathBuf>,
     output_path: Option<PathBuf>,
@@ -15,69 +16,554 @@ pub(crate) fn output_lazyframe(
 ) -> Result<(), TblCliError> {
     match output_mode {
         OutputMode::PrintToStdout => print_lazyframe(lf, args),
-        OutputMode::SaveToSingleFile => save_lf_to_disk(lf, output_path, args),
-        OutputMode::SaveToDirectory => save_lf_to_disk(lf, output_path, args),
-        OutputMode::ModifyInplace => save_lf_to_disk(lf, output_path, args),
-        OutputMode::Partition => partition_data(lf, input_paths, args),
+        OutputMode::SaveToSingleFile => save_lf_to_disk(lf, &input_paths, output_path, args).await,
+        OutputMode::SaveToDirectory => save_lf_to_disk(lf, &input_paths, output_path, args).await,
+        OutputMode::ModifyInplace => save_lf_to_disk(lf, &input_paths, output_path, args).await,
+        OutputMode::Partition => partition_data(lf, &input_paths, args).await,
         OutputMode::InteractiveLf => enter_interactive_session(lf, input_paths, args),
         OutputMode::InteractiveDf => enter_interactive_session(lf, input_paths, args),
+        OutputMode::Tui => crate::tui::run_viewer(lf),
     }
 }
 
 fn print_lazyframe(lf: LazyFrame, args: &DataArgs) -> Result<(), TblCliError> {
-    let df = lf.collect()?;
-
-    let mut df = match args.hex {
-        true => binary_to_hex(&mut df.clone())?,
-        false => df,
-    };
+    if args.parquet {
+        return write_parquet_to_stdout(lf, args);
+    }
 
-    if !args.no_summary {
+    if !args.no_summary && !args.ipc {
         println!();
         println!();
         tbl_core::formats::print_header("Data");
     };
 
-    let n_show = match &args.n {
-        Some(n) if n == "all" => df.height(),
-        Some(n) => n.parse::<usize>()?,
-        None => 20,
+    // push row limit down into the scan, reading one extra row to tell if rows remain
+    let n_show = get_n_show(args)?;
+    let mut limited_lf = match n_show {
+        Some(n_show) => lf.limit((n_show + 1) as IdxSize),
+        None => lf,
     };
-    let n_missing = if df.height() >= n_show {
-        df.height() - n_show
-    } else {
-        0
+    let schema = limited_lf.schema()?;
+    let df = limited_lf.collect()?;
+    let more = matches!(n_show, Some(n_show) if df.height() > n_show);
+    let df = match n_show {
+        Some(n_show) if more => df.head(Some(n_show)),
+        _ => df,
     };
 
-    if args.csv {
-        let df = binary_to_hex(&mut df)?;
-        print_dataframe_as_csv(&df, n_show)?;
-    } else if args.json | args.jsonl {
-        let df = binary_to_hex(&mut df)?;
-        print_dataframe_as_json(&df, n_show, args.jsonl)?;
+    if let Some(mut writer) = BatchedStdoutWriter::new(&schema, args)? {
+        writer.write_batch(df)?;
+        writer.finish()?;
+        if args.ipc {
+            return Ok(());
+        }
     } else {
-        let df = df.head(Some(n_show));
-        println!("{}", df);
+        let mut df = match args.hex {
+            true => binary_to_hex(&mut df.clone())?,
+            false => df,
+        };
+        if args.json {
+            let df = binary_to_hex(&mut df)?;
+            print_dataframe_as_json(&df, df.height(), false)?;
+        } else {
+            println!("{}", df);
+        };
+    }
+
+    print_more_rows(more);
+    Ok(())
+}
+
+/// stream stdout output one row group at a time, so that memory stays bounded
+///
+/// only used when every transformation acts on rows independently, so that
+/// transforming each row group separately gives the same rows as transforming all
+pub(crate) async fn stream_files_to_stdout(
+    input_paths: &[PathBuf],
+    hive_roots: &[PathBuf],
+    args: &DataArgs,
+) -> Result<(), TblCliError> {
+    if !args.no_summary && !args.ipc {
+        println!();
+        println!();
+        tbl_core::formats::print_header("Data");
+    };
+
+    let frames = input_paths
+        .iter()
+        .map(|path| {
+            let lf = tbl_core::parquet::create_lazyframe(std::slice::from_ref(path), hive_roots)?;
+            crate::transform::apply_transformations(lf, args)
+        })
+        .collect::<Result<Vec<LazyFrame>, TblCliError>>()?;
+    let schema = match frames.first() {
+        Some(lf) => lf.clone().schema()?,
+        None => return Ok(()),
     };
+    let mut writer = BatchedStdoutWriter::new(&schema, args)?
+        .ok_or_else(|| TblCliError::Error("format cannot be streamed".to_string()))?;
+
+    let n_show = get_n_show(args)?;
+    let mut n_written = 0;
+    let mut more = false;
+    'files: for (path, lf) in input_paths.iter().zip(frames.iter()) {
+        // every batch is written under the columns of the first file
+        if lf.clone().schema()? != schema {
+            return Err(TblCliError::Error(format!(
+                "schema of {} differs from schema of {}, cannot stream them as one output",
+                path.display(),
+                input_paths[0].display()
+            )));
+        }
+
+        let metadata = tbl_core::parquet::get_parquet_metadata(path).await?;
+        let mut offset = 0;
+        for row_group in metadata.row_groups().iter() {
+            let n_rows = row_group.num_rows();
+            let lf = tbl_core::parquet::create_lazyframe(std::slice::from_ref(path), hive_roots)?
+                .slice(offset, n_rows as IdxSize);
+            offset += n_rows;
+            let lf = crate::transform::apply_transformations(lf, args)?;
+            // read one row past the limit to tell if rows remain
+            let df = match n_show {
+                Some(n_show) => lf.limit((n_show - n_written + 1) as IdxSize).collect()?,
+                None => lf.collect()?,
+            };
+            if let Some(n_show) = n_show {
+                if n_written + df.height() > n_show {
+                    more = true;
+                    let df = df.head(Some(n_show - n_written));
+                    if df.height() > 0 {
+                        writer.write_batch(df)?;
+                    }
+                    break 'files;
+                }
+            }
+            n_written += df.height();
+            writer.write_batch(df)?;
+        }
+    }
+    writer.finish()?;
+
+    if !args.ipc {
+        print_more_rows(more);
+    }
+    Ok(())
+}
+
+/// return true if stdout output can be streamed file by file
+pub(crate) fn can_stream_files_to_stdout(args: &DataArgs) -> bool {
+    let streamable_format = args.csv
+        | args.jsonl
+        | args.ipc
+        | matches!(
+            args.format.as_deref(),
+            Some("tsv") | Some("sql") | Some("markdown") | Some("plain")
+        );
+    let row_local = args.sort.is_none()
+        && args.unique.is_none()
+        && args.value_counts.is_none()
+        && args.head.is_none()
+        && args.tail.is_none()
+        && args.offset.is_none()
+        && args.sample.is_none()
+        && args.sample_frac.is_none();
+    streamable_format && row_local
+}
+
+/// number of rows to print, none for all rows
+fn get_n_show(args: &DataArgs) -> Result<Option<usize>, TblCliError> {
+    match args.n.as_deref() {
+        Some("all") => Ok(None),
+        Some(n) => Ok(Some(n.parse::<usize>()?)),
+        None if args.ipc => Ok(None),
+        None => Ok(Some(20)),
+    }
+}
 
-    if n_missing > 0 {
+/// note that rows beyond the printed limit were not shown, without counting them
+fn print_more_rows(more: bool) {
+    if more {
         println!(
-            "{} rows omitted, use {} to show all rows",
-            n_missing.to_string().colorize_constant().bold(),
+            "more rows available, use {} to show all rows",
             cstr!("<white><bold>-n all</bold></white>")
         );
     }
+}
 
-    Ok(())
+/// writes csv, tsv, json lines, sql, markdown, plain, or arrow ipc stream output
+/// to stdout one batch at a time
+enum BatchedStdoutWriter {
+    Csv {
+        stdout: BufWriter<StdoutLock<'static>>,
+        separator: u8,
+        include_header: bool,
+    },
+    Sql {
+        stdout: BufWriter<StdoutLock<'static>>,
+        table: String,
+    },
+    Jsonl {
+        stdout: BufWriter<StdoutLock<'static>>,
+    },
+    Markdown {
+        stdout: BufWriter<StdoutLock<'static>>,
+    },
+    Plain {
+        stdout: BufWriter<StdoutLock<'static>>,
+        names: Vec<String>,
+        numeric: Vec<bool>,
+        widths: Option<Vec<usize>>,
+    },
+    Ipc {
+        writer: StreamWriter<BufWriter<StdoutLock<'static>>>,
+        hex: bool,
+    },
 }
 
-fn print_dataframe_as_csv(df: &DataFrame, n: usize) -> Result<(), PolarsError> {
-    let mut writer = CsvWriter::new(stdout());
-    let df: DataFrame = df.head(Some(n));
-    writer.finish(&mut df.clone())
+impl BatchedStdoutWriter {
+    /// create writer for the stdout format of args, or none if format is not batched
+    fn new(schema: &Schema, args: &DataArgs) -> Result<Option<Self>, TblCliError> {
+        let stdout = BufWriter::new(stdout().lock());
+        let format = args.format.as_deref();
+        if args.csv | (format == Some("tsv")) {
+            Ok(Some(BatchedStdoutWriter::Csv {
+                stdout,
+                separator: match (args.delimiter, args.csv) {
+                    (Some(delimiter), _) => delimiter as u8,
+                    (None, true) => b',',
+                    (None, false) => b'\t',
+                },
+                include_header: !args.no_header,
+            }))
+        } else if format == Some("sql") {
+            Ok(Some(BatchedStdoutWriter::Sql {
+                stdout,
+                table: args.sql_table.clone(),
+            }))
+        } else if args.jsonl {
+            Ok(Some(BatchedStdoutWriter::Jsonl { stdout }))
+        } else if format == Some("markdown") {
+            let mut stdout = stdout;
+            let names: Vec<&str> = schema.iter_names().map(|name| name.as_str()).collect();
+            write!(stdout, "{}", render_markdown_header(&names))?;
+            Ok(Some(BatchedStdoutWriter::Markdown { stdout }))
+        } else if format == Some("plain") {
+            let schema = hex_schema(schema);
+            Ok(Some(BatchedStdoutWriter::Plain {
+                stdout,
+                names: schema.iter_names().map(|name| name.to_string()).collect(),
+                numeric: schema
+                    .iter_dtypes()
+                    .map(|dtype| dtype.is_numeric())
+                    .collect(),
+                widths: None,
+            }))
+        } else if args.ipc {
+            if std::io::stdout().is_terminal() {
+                return Err(TblCliError::Arg(
+                    "refusing to write binary data to a terminal, pipe or redirect stdout"
+                        .to_string(),
+                ));
+            }
+            let schema = match args.hex {
+                true => hex_schema(schema),
+                false => schema.clone(),
+            };
+            let mut writer = StreamWriter::new(stdout, WriteOptions { compression: None });
+            writer.start(&schema.to_arrow(true), None)?;
+            Ok(Some(BatchedStdoutWriter::Ipc {
+                writer,
+                hex: args.hex,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn write_batch(&mut self, mut df: DataFrame) -> Result<(), TblCliError> {
+        match self {
+            BatchedStdoutWriter::Csv {
+                stdout,
+                separator,
+                include_header,
+            } => {
+                let mut df = binary_to_hex(&mut df)?;
+                CsvWriter::new(stdout)
+                    .with_separator(*separator)
+                    .include_header(*include_header)
+                    .finish(&mut df)?;
+                *include_header = false;
+            }
+            BatchedStdoutWriter::Sql { stdout, table } => {
+                let df = binary_to_hex(&mut df)?;
+                write!(stdout, "{}", render_sql_inserts(&df, table)?)?;
+            }
+            BatchedStdoutWriter::Jsonl { stdout } => {
+                let mut df = binary_to_hex(&mut df)?;
+                JsonWriter::new(stdout)
+                    .with_json_format(JsonFormat::JsonLines)
+                    .finish(&mut df)?;
+            }
+            BatchedStdoutWriter::Markdown { stdout } => {
+                let df = binary_to_hex(&mut df)?;
+                write!(stdout, "{}", render_markdown_rows(&df)?)?;
+            }
+            BatchedStdoutWriter::Plain {
+                stdout,
+                names,
+                numeric,
+                widths,
+            } => {
+                let df = binary_to_hex(&mut df)?;
+                let columns = get_string_columns(&df)?;
+                let batch_widths = get_plain_widths(names, &columns);
+                // widths come from the first batch, later batches only widen columns
+                let batch_widths = match widths.take() {
+                    Some(widths) => widths
+                        .into_iter()
+                        .zip(batch_widths)
+                        .map(|(width, batch_width)| width.max(batch_width))
+                        .collect(),
+                    None => {
+                        let names = names.iter().map(|name| name.as_str()).collect();
+                        write!(
+                            stdout,
+                            "{}",
+                            render_plain_row(names, &batch_widths, numeric)
+                        )?;
+                        batch_widths
+                    }
+                };
+                write!(
+                    stdout,
+                    "{}",
+                    render_plain_rows(&columns, &batch_widths, numeric)
+                )?;
+                *widths = Some(batch_widths);
+            }
+            BatchedStdoutWriter::Ipc { writer, hex } => {
+                let mut df = match hex {
+                    true => binary_to_hex(&mut df)?,
+                    false => df,
+                };
+                // record batches need every column split into the same chunks
+                df.align_chunks();
+                for batch in df.iter_chunks(true, true) {
+                    writer.write(&batch, None)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), TblCliError> {
+        match self {
+            BatchedStdoutWriter::Csv { mut stdout, .. }
+            | BatchedStdoutWriter::Sql { mut stdout, .. }
+            | BatchedStdoutWriter::Jsonl { mut stdout }
+            | BatchedStdoutWriter::Markdown { mut stdout } => stdout.flush()?,
+            BatchedStdoutWriter::Plain {
+                mut stdout,
+                names,
+                numeric,
+                widths,
+            } => {
+                // header of output without rows
+                if widths.is_none() {
+                    let widths = get_plain_widths(&names, &[]);
+                    let names = names.iter().map(|name| name.as_str()).collect();
+                    write!(stdout, "{}", render_plain_row(names, &widths, &numeric))?;
+                }
+                stdout.flush()?
+            }
+            BatchedStdoutWriter::Ipc { mut writer, .. } => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+/// get cells of each column as strings, with none for nulls
+pub(crate) fn get_string_columns(df: &DataFrame) -> Result<Vec<Vec<Option<String>>>, TblCliError> {
+    let mut columns = Vec::new();
+    for series in df.get_columns() {
+        let values = match series.cast(&DataType::String) {
+            Ok(strings) => strings
+                .str()?
+                .into_iter()
+                .map(|value| value.map(|value| value.to_string()))
+                .collect(),
+            Err(_) => series
+                .iter()
+                .map(|value| match value {
+                    AnyValue::Null => None,
+                    value => Some(value.to_string()),
+                })
+                .collect(),
+        };
+        columns.push(values);
+    }
+    Ok(columns)
+}
+
+/// escape cell of a markdown table
+fn escape_markdown(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// render header and separator lines of a github-flavored markdown table
+fn render_markdown_header(names: &[&str]) -> String {
+    let names: Vec<String> = names.iter().map(|name| escape_markdown(name)).collect();
+    let mut output = format!("| {} |\n", names.join(" | "));
+    output.push_str(&format!("|{}\n", " --- |".repeat(names.len())));
+    output
+}
+
+/// render rows of dataframe as lines of a github-flavored markdown table
+fn render_markdown_rows(df: &DataFrame) -> Result<String, TblCliError> {
+    let columns = get_string_columns(df)?;
+    let mut output = String::new();
+    for row in 0..df.height() {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| escape_markdown(column[row].as_deref().unwrap_or_default()))
+            .collect();
+        output.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    Ok(output)
+}
+
+/// get width of each plain text column, fitting its name and its values
+fn get_plain_widths(names: &[String], columns: &[Vec<Option<String>>]) -> Vec<usize> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(c, name)| {
+            columns
+                .get(c)
+                .into_iter()
+                .flatten()
+                .map(|value| value.as_deref().unwrap_or("null").chars().count())
+                .chain(std::iter::once(name.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// render line of fixed-width text without box drawing, numbers right aligned
+fn render_plain_row(cells: Vec<&str>, widths: &[usize], numeric: &[bool]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .zip(widths.iter().zip(numeric.iter()))
+        .map(|(cell, (width, numeric))| match numeric {
+            true => format!("{:>width$}", cell, width = width),
+            false => format!("{:<width$}", cell, width = width),
+        })
+        .collect();
+    format!("{}\n", cells.join("  ").trim_end())
+}
+
+/// render string columns as lines of fixed-width text
+fn render_plain_rows(
+    columns: &[Vec<Option<String>>],
+    widths: &[usize],
+    numeric: &[bool],
+) -> String {
+    let n_rows = columns.first().map(|column| column.len()).unwrap_or(0);
+    let mut output = String::new();
+    for row in 0..n_rows {
+        let cells = columns
+            .iter()
+            .map(|column| column[row].as_deref().unwrap_or("null"))
+            .collect();
+        output.push_str(&render_plain_row(cells, widths, numeric));
+    }
+    output
+}
+
+/// render dataframe as sql insert statements, one per row
+fn render_sql_inserts(df: &DataFrame, table: &str) -> Result<String, TblCliError> {
+    let quote_identifier = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+    let columns = get_string_columns(df)?;
+    let unquoted: Vec<bool> = df
+        .dtypes()
+        .iter()
+        .map(|dtype| dtype.is_numeric() || matches!(dtype, DataType::Boolean))
+        .collect();
+    let names: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| quote_identifier(name))
+        .collect();
+    let prefix = format!(
+        "INSERT INTO {} ({}) VALUES",
+        quote_identifier(table),
+        names.join(", ")
+    );
+    let mut output = String::new();
+    for row in 0..df.height() {
+        let values: Vec<String> = columns
+            .iter()
+            .zip(unquoted.iter())
+            .map(|(column, unquoted)| match (&column[row], unquoted) {
+                (None, _) => "NULL".to_string(),
+                (Some(value), true) if is_non_finite(value) => "NULL".to_string(),
+                (Some(value), true) => value.to_uppercase(),
+                (Some(value), false) => format!("'{}'", value.replace('\'', "''")),
+            })
+            .collect();
+        output.push_str(&format!("{} ({});\n", prefix, values.join(", ")));
+    }
+    Ok(output)
+}
+
+/// return true if value is a float nan or infinity, which sql has no literal for
+fn is_non_finite(value: &str) -> bool {
+    value
+        .parse::<f64>()
+        .map(|value| !value.is_finite())
+        .unwrap_or(false)
+}
+
+/// schema after binary columns are encoded as hex strings
+fn hex_schema(schema: &Schema) -> Schema {
+    schema
+        .iter()
+        .map(|(name, dtype)| match dtype {
+            DataType::Binary => Field::new(name, DataType::String),
+            dtype => Field::new(name, dtype.clone()),
+        })
+        .collect()
+}
+
+/// write parquet data to stdout, for piping into other tools
+///
+/// all rows are written unless limited with -n
+fn write_parquet_to_stdout(lf: LazyFrame, args: &DataArgs) -> Result<(), TblCliError> {
+    if std::io::stdout().is_terminal() {
+        return Err(TblCliError::Arg(
+            "refusing to write binary data to a terminal, pipe or redirect stdout".to_string(),
+        ));
+    }
+    let lf = match args.n.as_deref() {
+        None | Some("all") => lf,
+        Some(n) => lf.limit(n.parse::<IdxSize>()?),
+    };
+    let mut df = lf.collect()?;
+    let mut stdout = BufWriter::new(stdout().lock());
+    ParquetWriter::new(&mut stdout)
+        .with_compression(ParquetCompression::Snappy)
+        .finish(&mut df)?;
+    stdout.flush()?;
+    Ok(())
 }
 
-fn print_dataframe_as_json(df: &DataFrame, n: usize, jsonl: bool) -> Result<(), PolarsError> {
+pub(crate) fn print_dataframe_as_json(
+    df: &DataFrame,
+    n: usize,
+    jsonl: bool,
+) -> Result<(), PolarsError> {
     let mut writer = JsonWriter::new(stdout());
 
     if !jsonl {
@@ -94,7 +580,7 @@ fn print_dataframe_as_json(df: &DataFrame, n: usize, jsonl: bool) -> Result<(),
     result
 }
 
-fn binary_to_hex(df: &mut DataFrame) -> Result<DataFrame, PolarsError> {
+pub(crate) fn binary_to_hex(df: &mut DataFrame) -> Result<DataFrame, PolarsError> {
     let mut df = df.clone();
 
     let binary_columns: Vec<String> = df
@@ -127,8 +613,9 @@ fn binary_to_hex(df: &mut DataFrame) -> Result<DataFrame, PolarsError> {
     Ok(df)
 }
 
-fn save_lf_to_disk(
+async fn save_lf_to_disk(
     lf: LazyFrame,
+    input_paths: &[PathBuf],
     output_path: Option<PathBuf>,
     args: &DataArgs,
 ) -> Result<(), TblCliError> {
@@ -136,56 +623,408 @@ fn save_lf_to_disk(
         Some(output_path) => output_path,
         None => return Err(TblCliError::Error("no output path specified".to_string())),
     };
+    let file_name = output_path
+        .file_name()
+        .ok_or_else(|| TblCliError::Error("File name is missing".to_string()))?
+        .to_str()
+        .ok_or_else(|| TblCliError::Error("File name is not valid UTF-8".to_string()))?;
 
-    // Create a temporary path by appending "_tmp" to the original path
-    let tmp_path = output_path.with_file_name(format!(
-        "{}_tmp",
-        output_path
-            .file_name()
-            .ok_or_else(|| TblCliError::Error("File name is missing".to_string()))?
-            .to_str()
-            .ok_or_else(|| TblCliError::Error("File name is not valid UTF-8".to_string()))?
-    ));
-
-    // Write to the temporary file
-    if output_path.ends_with(".csv") | args.csv {
-        let options = CsvWriterOptions::default();
-        lf.sink_csv(&tmp_path, options)?;
-    } else if output_path.ends_with(".json") | args.json {
-        let options = JsonWriterOptions::default();
-        lf.sink_json(&tmp_path, options)?;
+    // Create a temporary path by appending "_tmp" to the original path,
+    // remote outputs are staged in the local temp dir and then uploaded
+    let remote = tbl_core::filesystem::is_remote_path(&output_path);
+    let tmp_path = if remote {
+        std::env::temp_dir().join(format!("{}_{}_tmp", std::process::id(), file_name))
     } else {
-        let options = ParquetWriteOptions::default();
-        let result = lf.clone().sink_parquet(&tmp_path, options);
-        if result.is_err() {
-            // sink_parquet() is still missing some options, so if it fails use backup
-            let file = std::fs::File::create(&tmp_path)?;
-            let writer = ParquetWriter::new(file)
-                .with_compression(ParquetCompression::Snappy)
-                .with_statistics(StatisticsOptions {
-                    min_value: true,
-                    max_value: true,
-                    distinct_count: true,
-                    null_count: true,
-                });
-            writer.finish(&mut lf.clone().collect()?)?;
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        output_path.with_file_name(format!("{}_tmp", file_name))
     };
 
+    // Write to the temporary file, removing it if any step fails
+    let format = get_output_file_format(&output_path, args)?;
+    let layout = match format {
+        OutputFileFormat::Parquet => get_preserved_layout(input_paths, args).await?,
+        _ => None,
+    };
+    let lf = match &layout {
+        Some(layout) if args.columns.is_none() => restore_column_order(lf, &layout.column_names)?,
+        _ => lf,
+    };
+    if format == OutputFileFormat::Parquet {
+        validate_bloom_filter_columns(&lf, args)?;
+    }
+    let result = write_tmp_file(lf, &tmp_path, format, layout.as_ref(), input_paths, args).await;
+    if result.is_err() && tmp_path.exists() {
+        std::fs::remove_file(&tmp_path)?;
+    }
+    result?;
+
     // Move the temporary file to the final output path
-    std::fs::rename(&tmp_path, &output_path).map_err(|e| TblCliError::Error(e.to_string()))?;
+    if remote {
+        let result = tbl_core::filesystem::upload_file(&tmp_path, &output_path).await;
+        std::fs::remove_file(&tmp_path)?;
+        result?;
+    } else {
+        std::fs::rename(&tmp_path, &output_path).map_err(|e| TblCliError::Error(e.to_string()))?;
+    }
 
     Ok(())
 }
 
-fn partition_data(
-    _lf: LazyFrame,
-    _input_paths: Vec<PathBuf>,
-    _args: &DataArgs,
+async fn write_tmp_file(
+    lf: LazyFrame,
+    tmp_path: &Path,
+    format: OutputFileFormat,
+    layout: Option<&ParquetLayout>,
+    input_paths: &[PathBuf],
+    args: &DataArgs,
+) -> Result<(), TblCliError> {
+    write_lf_to_file(lf, tmp_path, format, layout, args)?;
+    if format == OutputFileFormat::Parquet {
+        write_key_value_metadata(tmp_path, input_paths, args).await?;
+    }
+    if let Some(layout) = layout {
+        tbl_core::parquet::update_parquet_layout(
+            tmp_path,
+            &layout.sorting_columns,
+            &layout.field_metadata,
+        )?;
+    }
+    Ok(())
+}
+
+/// check that columns of --bloom-filter exist in output before writing it,
+/// nested columns are named by their root column
+fn validate_bloom_filter_columns(lf: &LazyFrame, args: &DataArgs) -> Result<(), TblCliError> {
+    let columns = match &args.bloom_filter {
+        Some(columns) if !columns.is_empty() => columns,
+        _ => return Ok(()),
+    };
+    let schema = lf.clone().schema()?;
+    for column in columns.iter() {
+        let root = column.split('.').next().unwrap_or_default();
+        if !schema.contains(column) && !schema.contains(root) {
+            return Err(TblCliError::Arg(format!(
+                "--bloom-filter column '{}' is not in output",
+                column
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// write key-value metadata shared by parquet inputs into output, applying
+/// --set-metadata and --drop-metadata
+async fn write_key_value_metadata(
+    path: &Path,
+    input_paths: &[PathBuf],
+    args: &DataArgs,
 ) -> Result<(), TblCliError> {
-    Err(TblCliError::Error(
-        "partition functionality not implemented".to_string(),
-    ))
+    let parquet_inputs: Vec<PathBuf> = input_paths
+        .iter()
+        .filter(|path| OutputFileFormat::from_path(path) == Some(OutputFileFormat::Parquet))
+        .cloned()
+        .collect();
+    let mut set = if parquet_inputs.is_empty() || parquet_inputs.len() < input_paths.len() {
+        Vec::new()
+    } else {
+        let per_file = tbl_core::parquet::get_parquet_key_value_metadatas(&parquet_inputs).await?;
+        tbl_core::parquet::get_common_key_value_metadata(&per_file)
+    };
+    for (key, value) in parse_set_metadata(args)?.into_iter() {
+        set.retain(|(existing, _)| existing != &key);
+        set.push((key, Some(value)));
+    }
+    let drop = args.drop_metadata.clone().unwrap_or_default();
+    if set.is_empty() && drop.is_empty() {
+        return Ok(());
+    }
+    tbl_core::parquet::update_parquet_key_value_metadata(path, &set, &drop)?;
+    Ok(())
+}
+
+/// parse KEY=VALUE entries of --set-metadata
+pub(crate) fn parse_set_metadata(args: &DataArgs) -> Result<Vec<(String, String)>, TblCliError> {
+    args.set_metadata
+        .iter()
+        .flatten()
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) => Ok((key.to_string(), value.to_string())),
+            None => Err(TblCliError::Arg(format!(
+                "invalid --set-metadata {}, use KEY=VALUE",
+                entry
+            ))),
+        })
+        .collect()
+}
+
+/// get layout of the parquet file rewritten with --preserve, with column names
+/// as they appear in the output
+async fn get_preserved_layout(
+    input_paths: &[PathBuf],
+    args: &DataArgs,
+) -> Result<Option<ParquetLayout>, TblCliError> {
+    let input_path = match input_paths {
+        [input_path] if args.preserve => input_path,
+        _ => return Ok(None),
+    };
+    // files without a known extension are parquet if their footer can be read
+    let mut layout = match OutputFileFormat::from_path(input_path) {
+        Some(OutputFileFormat::Parquet) => {
+            tbl_core::parquet::get_parquet_layout(input_path).await?
+        }
+        Some(_) => return Ok(None),
+        None => match tbl_core::parquet::get_parquet_layout(input_path).await {
+            Ok(layout) => layout,
+            Err(_) => return Ok(None),
+        },
+    };
+
+    // rows stay sorted by a column unless it is reordered, dropped, or its values change
+    let mut changed: Vec<String> = Vec::new();
+    changed.extend(args.drop.iter().flatten().cloned());
+    for spec in args.with_columns.iter().flatten() {
+        changed.push(spec.split(':').next().unwrap_or_default().to_string());
+    }
+    if let Some(columns) = &args.columns {
+        changed.extend(
+            layout
+                .sorting_columns
+                .iter()
+                .filter(|column| !columns.contains(&column.name))
+                .map(|column| column.name.clone()),
+        );
+    }
+    changed.extend(args.nullify.iter().flatten().cloned());
+    for entry in args.cast.iter().chain(args.set.iter()).flatten() {
+        changed.push(entry.split('=').next().unwrap_or_default().to_string());
+    }
+    for entry in args.replace.iter().flatten() {
+        changed.push(entry.split('.').next().unwrap_or_default().to_string());
+    }
+    layout.sorting_columns = if let Some(sort) = &args.sort {
+        sort.iter()
+            .map(|column| SortedColumn {
+                name: column.strip_suffix(":desc").unwrap_or(column).to_string(),
+                descending: column.ends_with(":desc"),
+                nulls_first: true,
+            })
+            .collect()
+    } else if args.value_counts.is_some() || args.sample.is_some() || args.sample_frac.is_some() {
+        Vec::new()
+    } else {
+        layout
+            .sorting_columns
+            .into_iter()
+            .take_while(|column| !changed.contains(&column.name))
+            .collect()
+    };
+    if args.value_counts.is_some() {
+        layout.column_names.clear();
+        layout.field_metadata.clear();
+    }
+
+    // follow renamed columns
+    let renames: Vec<(&str, &str)> = args
+        .rename
+        .iter()
+        .flatten()
+        .filter_map(|rename| rename.split_once('='))
+        .collect();
+    let rename = |name: &mut String| {
+        if let Some((_, new)) = renames.iter().find(|(old, _)| old == name) {
+            *name = new.to_string();
+        }
+    };
+    layout.column_names.iter_mut().for_each(rename);
+    layout
+        .sorting_columns
+        .iter_mut()
+        .for_each(|column| rename(&mut column.name));
+    layout
+        .field_metadata
+        .iter_mut()
+        .for_each(|(name, _)| rename(name));
+
+    Ok(Some(layout))
+}
+
+/// put columns of the input back into their input order, leaving other columns in place
+fn restore_column_order(
+    mut lf: LazyFrame,
+    column_names: &[String],
+) -> Result<LazyFrame, TblCliError> {
+    let current: Vec<String> = lf.schema()?.iter_names().map(|n| n.to_string()).collect();
+    let mut preserved = column_names.iter().filter(|name| current.contains(name));
+    let ordered: Vec<String> = current
+        .iter()
+        .map(|name| match column_names.contains(name) {
+            true => preserved.next().unwrap_or(name).clone(),
+            false => name.clone(),
+        })
+        .collect();
+    if ordered == current {
+        return Ok(lf);
+    }
+    let exprs: Vec<Expr> = ordered.iter().map(|name| col(name)).collect();
+    Ok(lf.select(&exprs))
+}
+
+/// get parquet compression from --compression, or from the preserved layout
+fn get_parquet_compression(
+    layout: Option<&ParquetLayout>,
+    args: &DataArgs,
+) -> Result<Option<ParquetCompression>, TblCliError> {
+    match &args.compression {
+        Some(compression) => Ok(Some(parse_compression(compression)?)),
+        None => Ok(layout
+            .and_then(|layout| layout.compression)
+            .and_then(tbl_core::parquet::get_polars_compression)),
+    }
+}
+
+/// parse name of a parquet compression codec
+pub(crate) fn parse_compression(name: &str) -> Result<ParquetCompression, TblCliError> {
+    match name.to_lowercase().as_str() {
+        "uncompressed" | "none" => Ok(ParquetCompression::Uncompressed),
+        "snappy" => Ok(ParquetCompression::Snappy),
+        "gzip" => Ok(ParquetCompression::Gzip(None)),
+        "brotli" => Ok(ParquetCompression::Brotli(None)),
+        "zstd" => Ok(ParquetCompression::Zstd(None)),
+        "lz4" => Ok(ParquetCompression::Lz4Raw),
+        _ => Err(TblCliError::Arg(format!(
+            "unknown compression {}, use uncompressed, snappy, gzip, brotli, zstd, or lz4",
+            name
+        ))),
+    }
+}
+
+/// get format of output file from --output-format, output flags, or file extension
+pub(crate) fn get_output_file_format(
+    output_path: &Path,
+    args: &DataArgs,
+) -> Result<OutputFileFormat, TblCliError> {
+    if let Some(name) = &args.output_format {
+        return OutputFileFormat::from_name(name).ok_or_else(|| {
+            TblCliError::Arg(format!(
+                "unknown output format {}, use parquet, csv, tsv, json, jsonl, or ipc",
+                name
+            ))
+        });
+    }
+    let flag_format = if args.csv {
+        Some(OutputFileFormat::Csv)
+    } else if args.json {
+        Some(OutputFileFormat::Json)
+    } else if args.jsonl {
+        Some(OutputFileFormat::Jsonl)
+    } else if args.ipc {
+        Some(OutputFileFormat::Ipc)
+    } else {
+        None
+    };
+    let extension_format = OutputFileFormat::from_path(output_path);
+    Ok(flag_format
+        .or(extension_format)
+        .unwrap_or(OutputFileFormat::Parquet))
+}
+
+/// csv options from --delimiter, --no-header, and datetime format arguments
+fn get_csv_writer_options(format: OutputFileFormat, args: &DataArgs) -> CsvWriterOptions {
+    let separator = match (args.delimiter, format) {
+        (Some(delimiter), _) => delimiter as u8,
+        (None, OutputFileFormat::Tsv) => b'\t',
+        (None, _) => b',',
+    };
+    CsvWriterOptions {
+        include_header: !args.no_header,
+        serialize_options: SerializeOptions {
+            separator,
+            datetime_format: args.datetime_format.clone(),
+            date_format: args.date_format.clone(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// write lazyframe to file in format, with write options from args and preserved layout
+fn write_lf_to_file(
+    lf: LazyFrame,
+    path: &Path,
+    format: OutputFileFormat,
+    layout: Option<&ParquetLayout>,
+    args: &DataArgs,
+) -> Result<(), TblCliError> {
+    let options = FileWriteOptions {
+        csv: get_csv_writer_options(format, args),
+        compression: get_parquet_compression(layout, args)?,
+        row_group_size: args
+            .row_group_size
+            .or(layout.and_then(|layout| layout.row_group_size)),
+        bloom_filter_columns: args.bloom_filter.clone().unwrap_or_default(),
+        page_index: args.page_index || args.bloom_filter.is_some(),
+    };
+    Ok(tbl_core::writers::write_lf_to_file(
+        lf, path, format, &options,
+    )?)
+}
+
+async fn partition_data(
+    lf: LazyFrame,
+    input_paths: &[PathBuf],
+    args: &DataArgs,
+) -> Result<(), TblCliError> {
+    let columns = match &args.partition {
+        Some(columns) if !columns.is_empty() => columns.clone(),
+        _ => return Err(TblCliError::Arg("no partition columns given".to_string())),
+    };
+    let output_dir = match &args.output_dir {
+        Some(output_dir) => output_dir.clone(),
+        None => std::env::current_dir()?,
+    };
+    let extension = get_output_file_format(Path::new("part-0"), args)?.extension();
+
+    // find partition keys first, then write each partition into its own
+    // key=value directory, so only one partition is in memory at a time
+    let keys = lf
+        .clone()
+        .select(columns.iter().map(|column| col(column)).collect::<Vec<_>>())
+        .unique_stable(None, UniqueKeepStrategy::First)
+        .collect()?;
+    let mut written = std::collections::HashSet::new();
+    for row in 0..keys.height() {
+        let mut partitions = Vec::new();
+        let mut predicate = lit(true);
+        for column in columns.iter() {
+            let value = keys.column(column)?.slice(row as i64, 1);
+            let string_value = value
+                .cast(&DataType::String)?
+                .str()?
+                .get(0)
+                .map(String::from);
+            // empty strings and nulls share the null partition directory
+            predicate = match string_value.as_deref() {
+                Some("") | None => predicate.and(
+                    col(column)
+                        .is_null()
+                        .or(col(column).cast(DataType::String).eq(lit(""))),
+                ),
+                Some(_) => predicate.and(col(column).eq(lit(value))),
+            };
+            partitions.push((column.clone(), string_value));
+        }
+        let partition_dir = tbl_core::filesystem::get_hive_partition_path(&output_dir, &partitions);
+        if !written.insert(partition_dir.clone()) {
+            continue;
+        }
+        let output_path = partition_dir.join(format!("part-0.{}", extension));
+        let part = lf.clone().filter(predicate).drop(columns.clone());
+        save_lf_to_disk(part, input_paths, Some(output_path), args).await?;
+    }
+
+    Ok(())
 }
 
 fn enter_interactive_session(
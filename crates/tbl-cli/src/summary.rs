@@ -1,10 +1,12 @@
 use crate::{DataArgs, OutputMode, TblCliError};
 use std::path::{Path, PathBuf};
-use tbl_core::formats::{print_bullet, print_header};
+use tbl_core::formats::{format_with_commas, print_bullet, print_header};
+use tbl_core::parquet::PruneSummary;
 
 pub(crate) async fn print_summary(
     inputs_and_outputs: &[(Vec<PathBuf>, Option<PathBuf>)],
     output_mode: &OutputMode,
+    pruning: Option<&PruneSummary>,
     args: &DataArgs,
 ) -> Result<(), TblCliError> {
     let mut n_input_files = 0;
@@ -19,9 +21,20 @@ pub(crate) async fn print_summary(
     }
 
     // compute total size of input files
-    let n_input_bytes = tbl_core::filesystem::get_total_bytes_of_files(&all_input_files).await?;
+    let file_paths: Vec<&Path> = all_input_files
+        .iter()
+        .filter(|path| !crate::inputs::is_stdin_path(path))
+        .copied()
+        .collect();
+    let n_input_bytes = tbl_core::filesystem::get_total_bytes_of_files(&file_paths).await?;
 
-    print_input_summary(n_input_files, &all_input_files, n_input_bytes, args);
+    print_input_summary(
+        n_input_files,
+        &all_input_files,
+        n_input_bytes,
+        pruning,
+        args,
+    );
     println!();
     println!();
     print_transform_summary(args);
@@ -35,6 +48,7 @@ fn print_input_summary(
     n_input_files: usize,
     input_files: &[&Path],
     n_input_bytes: u64,
+    pruning: Option<&PruneSummary>,
     _args: &DataArgs,
 ) {
     print_header("Inputs");
@@ -46,6 +60,20 @@ fn print_input_summary(
         "n_input_files",
         tbl_core::formats::format_with_commas(n_input_files as u64),
     );
+    if let Some(pruning) = pruning {
+        print_bullet(
+            "n_skipped_files",
+            format!(
+                "{} of {} files, {} of {} row groups, {} of {} rows ruled out by statistics",
+                format_with_commas(pruning.n_skipped_files),
+                format_with_commas(pruning.n_files),
+                format_with_commas(pruning.n_skipped_row_groups),
+                format_with_commas(pruning.n_row_groups),
+                format_with_commas(pruning.n_skipped_rows),
+                format_with_commas(pruning.n_rows),
+            ),
+        );
+    }
 
     let n_show_files = 10;
     for path in input_files.iter().take(n_show_files) {
@@ -60,6 +88,21 @@ fn print_input_summary(
 fn print_transform_summary(args: &DataArgs) {
     print_header("Transformations");
     let mut transforming = false;
+    if args.sample.is_some() || args.sample_frac.is_some() {
+        let mut size = match (args.sample, args.sample_frac) {
+            (Some(n), _) => format!("{} rows", n),
+            (_, Some(fraction)) => format!("{} of rows", fraction),
+            _ => String::new(),
+        };
+        if let Some(column) = &args.sample_by {
+            size = format!("{} per value of {}", size, column);
+        }
+        if let Some(seed) = args.seed {
+            size = format!("{}, seed {}", size, seed);
+        }
+        print_bullet("sampling rows", size);
+        transforming = true;
+    }
     if let Some(with_columns) = &args.with_columns {
         print_bullet("adding columns", format!("{:?}", with_columns));
         transforming = true;
@@ -76,6 +119,25 @@ fn print_transform_summary(args: &DataArgs) {
         print_bullet("casting types", format!("{:?}", cast));
         transforming = true;
     }
+    if let Some(unique) = &args.unique {
+        let columns = match unique.is_empty() {
+            true => "all columns".to_string(),
+            false => format!("{:?}", unique),
+        };
+        print_bullet(
+            "removing duplicate rows",
+            format!("{}, keep {}", columns, args.keep),
+        );
+        transforming = true;
+    }
+    if let Some(set_metadata) = &args.set_metadata {
+        print_bullet("setting metadata", format!("{:?}", set_metadata));
+        transforming = true;
+    }
+    if let Some(drop_metadata) = &args.drop_metadata {
+        print_bullet("dropping metadata", format!("{:?}", drop_metadata));
+        transforming = true;
+    }
     if !transforming {
         println!("[no transformations]");
     }
@@ -120,6 +182,12 @@ fn print_output_mode_summary(n_input_files: usize, output_mode: &OutputMode, arg
             print_bullet("output_mode", "REPARTITION");
             let summary = format!("repartitioning {} files", n_input_files);
             print_bullet("summary", summary);
+            if let Some(partition) = &args.partition {
+                print_bullet("partition_columns", format!("{:?}", partition));
+            }
+            if let Some(output_dir) = &args.output_dir {
+                print_bullet("output_dir", output_dir.to_string_lossy());
+            }
         }
         OutputMode::InteractiveLf => {
             print_bullet("output_mode", "INTERACTIVE");
@@ -137,5 +205,28 @@ fn print_output_mode_summary(n_input_files: usize, output_mode: &OutputMode, arg
             );
             print_bullet("summary", summary);
         }
+        OutputMode::Tui => {
+            print_bullet("output_mode", "TUI");
+            let summary = format!("loading {} files into terminal viewer", n_input_files);
+            print_bullet("summary", summary);
+        }
+    }
+    if let Some(compression) = &args.compression {
+        print_bullet("compression", compression);
+    }
+    if let Some(row_group_size) = args.row_group_size {
+        print_bullet("row_group_size", row_group_size.to_string());
+    }
+    if let Some(bloom_filter) = &args.bloom_filter {
+        print_bullet("bloom_filter_columns", format!("{:?}", bloom_filter));
+    }
+    if args.page_index || args.bloom_filter.is_some() {
+        print_bullet("page_index", "writing column and offset indexes");
+    }
+    if args.preserve {
+        print_bullet(
+            "preserve",
+            "keeping compression, row groups, sort order, and column metadata of inputs",
+        );
     }
 }
@@ -1,6 +1,7 @@
-use crate::{DataArgs, TblCliError};
+use crate::{DataArgs, TblCliError, UniqueKeep};
 use polars::prelude::*;
 use std::str::FromStr;
+use tbl_core::filters::{parse_column_filter, FilterOperator};
 
 pub(crate) fn apply_transformations(
     lf: LazyFrame,
@@ -13,6 +14,7 @@ pub(crate) fn apply_transformations(
     let lf = apply_set(lf, args.set.as_deref())?;
     let lf = apply_nullify(lf, args.nullify.as_deref())?;
     let lf = apply_replace(lf, args.replace.as_deref())?;
+    let lf = apply_unique(lf, args.unique.as_deref(), args.keep)?;
     let lf = apply_select(lf, args.columns.as_deref())?;
     let lf = apply_offset(lf, args.offset)?;
     let lf = apply_head(lf, args.head)?;
@@ -160,110 +162,45 @@ fn apply_single_filter(
     filter: &str,
     schema: &Schema,
 ) -> Result<LazyFrame, TblCliError> {
-    if filter.contains("!=") {
-        apply_comparison_filter(lf, filter, schema, "!=")
-    } else if filter.contains(">=") {
-        apply_comparison_filter(lf, filter, schema, ">=")
-    } else if filter.contains("<=") {
-        apply_comparison_filter(lf, filter, schema, "<=")
-    } else if filter.contains('=') {
-        apply_comparison_filter(lf, filter, schema, "=")
-    } else if filter.contains(">") {
-        apply_comparison_filter(lf, filter, schema, ">")
-    } else if filter.contains("<") {
-        apply_comparison_filter(lf, filter, schema, "<")
-    } else if filter.ends_with(".is_null") {
-        apply_null_filter(lf, filter, schema, true)
-    } else if filter.ends_with(".is_not_null") {
-        apply_null_filter(lf, filter, schema, false)
-    } else {
-        Err(TblCliError::Error("Invalid filter format".to_string()))
-    }
+    Ok(lf.filter(get_filter_expr(filter, schema)?))
 }
 
-fn apply_comparison_filter(
-    lf: LazyFrame,
-    filter: &str,
-    schema: &Schema,
-    operator: &str,
-) -> Result<LazyFrame, TblCliError> {
-    let parts: Vec<&str> = if operator == "=" {
-        filter.split('=').collect()
-    } else if operator == "!=" {
-        filter.split("!=").collect()
-    } else if operator == ">" {
-        filter.split('>').collect()
-    } else if operator == "<" {
-        filter.split('<').collect()
-    } else if operator == ">=" {
-        filter.split(">=").collect()
-    } else if operator == "<=" {
-        filter.split("<=").collect()
-    } else {
-        return Err(TblCliError::Error(format!(
-            "Invalid filter operator: {}",
-            operator
-        )));
-    };
-
-    if parts.len() != 2 {
-        return Err(TblCliError::Error("Invalid filter format".to_string()));
+/// build predicate expression of a filter such as `COLUMN=VALUE`
+pub(crate) fn get_filter_expr(filter: &str, schema: &Schema) -> Result<Expr, TblCliError> {
+    let filter = parse_column_filter(filter)?;
+    match (filter.operator, filter.value.as_deref()) {
+        (FilterOperator::IsNull, _) => get_null_filter_expr(&filter.column, schema, true),
+        (FilterOperator::IsNotNull, _) => get_null_filter_expr(&filter.column, schema, false),
+        (operator, Some(value)) => {
+            get_comparison_filter_expr(&filter.column, value, schema, operator)
+        }
+        (_, None) => Err(TblCliError::Error("Invalid filter format".to_string())),
     }
+}
 
-    let (column, value) = (parts[0], parts[1]);
+fn get_comparison_filter_expr(
+    column: &str,
+    value: &str,
+    schema: &Schema,
+    operator: FilterOperator,
+) -> Result<Expr, TblCliError> {
     let column_type = schema
         .get(column)
         .ok_or_else(|| TblCliError::Error(format!("Column '{}' not found", column)))?;
 
-    let filter_expr = match column_type {
+    let value_expr = match column_type {
         DataType::Binary => {
             if let Some(hex_value) = value.strip_prefix("0x") {
                 let binary_value = hex::decode(hex_value)
                     .map_err(|e| TblCliError::Error(format!("Invalid hex value: {}", e)))?;
-                if operator == "=" {
-                    col(column).eq(lit(binary_value))
-                } else if operator == "!=" {
-                    col(column).neq(lit(binary_value))
-                } else if operator == ">" {
-                    col(column).gt(lit(binary_value))
-                } else if operator == "<" {
-                    col(column).lt(lit(binary_value))
-                } else if operator == ">=" {
-                    col(column).gt_eq(lit(binary_value))
-                } else if operator == "<=" {
-                    col(column).lt_eq(lit(binary_value))
-                } else {
-                    return Err(TblCliError::Error(format!(
-                        "Invalid filter operator: {}",
-                        operator
-                    )));
-                }
+                lit(binary_value)
             } else {
                 return Err(TblCliError::Error(
                     "Binary value must start with 0x".to_string(),
                 ));
             }
         }
-        DataType::String => {
-            if operator == "=" {
-                col(column).eq(lit(value))
-            } else if operator == "!=" {
-                col(column).neq(lit(value))
-            } else if operator == ">" {
-                col(column).gt(lit(value))
-            } else if operator == "<" {
-                col(column).lt(lit(value))
-            } else if operator == ">=" {
-                col(column).gt_eq(lit(value))
-            } else if operator == "<=" {
-                col(column).lt_eq(lit(value))
-            } else {
-                return Err(TblCliError::Error(format!(
-                    "Invalid filter operator: {}",
-                    operator
-                )));
-            }
-        }
+        DataType::String => lit(value),
         DataType::UInt64 | DataType::Int64 | DataType::UInt32 | DataType::Int32 => {
             let int_value = if let Some(hex_value) = value.strip_prefix("0x") {
                 i64::from_str_radix(hex_value, 16)
@@ -273,24 +210,7 @@ fn apply_comparison_filter(
                     .parse::<i64>()
                     .map_err(|e| TblCliError::Error(format!("Invalid integer: {}", e)))?
             };
-            if operator == "=" {
-                col(column).eq(lit(int_value))
-            } else if operator == "!=" {
-                col(column).neq(lit(int_value))
-            } else if operator == ">" {
-                col(column).gt(lit(int_value))
-            } else if operator == "<" {
-                col(column).lt(lit(int_value))
-            } else if operator == ">=" {
-                col(column).gt_eq(lit(int_value))
-            } else if operator == "<=" {
-                col(column).lt_eq(lit(int_value))
-            } else {
-                return Err(TblCliError::Error(format!(
-                    "Invalid filter operator: {}",
-                    operator
-                )));
-            }
+            lit(int_value)
         }
         _ => {
             return Err(TblCliError::Error(format!(
@@ -300,17 +220,25 @@ fn apply_comparison_filter(
         }
     };
 
-    Ok(lf.filter(filter_expr))
-}
+    let filter_expr = match operator {
+        FilterOperator::Eq => col(column).eq(value_expr),
+        FilterOperator::NotEq => col(column).neq(value_expr),
+        FilterOperator::Gt => col(column).gt(value_expr),
+        FilterOperator::Lt => col(column).lt(value_expr),
+        FilterOperator::GtEq => col(column).gt_eq(value_expr),
+        FilterOperator::LtEq => col(column).lt_eq(value_expr),
+        FilterOperator::IsNull | FilterOperator::IsNotNull => {
+            return Err(TblCliError::Error(format!(
+                "Invalid filter operator: {:?}",
+                operator
+            )))
+        }
+    };
 
-fn apply_null_filter(
-    lf: LazyFrame,
-    filter: &str,
-    schema: &Schema,
-    is_null: bool,
-) -> Result<LazyFrame, TblCliError> {
-    let column = filter.trim_end_matches(if is_null { ".is_null" } else { ".is_not_null" });
+    Ok(filter_expr)
+}
 
+fn get_null_filter_expr(column: &str, schema: &Schema, is_null: bool) -> Result<Expr, TblCliError> {
     if schema.get(column).is_none() {
         return Err(TblCliError::Error(format!("Column '{}' not found", column)));
     }
@@ -321,7 +249,7 @@ fn apply_null_filter(
         col(column).is_not_null()
     };
 
-    Ok(lf.filter(filter_expr))
+    Ok(filter_expr)
 }
 
 pub(crate) fn apply_rename(
@@ -546,6 +474,29 @@ pub(crate) fn apply_replace(
     }
 }
 
+pub(crate) fn apply_unique(
+    lf: LazyFrame,
+    columns: Option<&[String]>,
+    keep: UniqueKeep,
+) -> Result<LazyFrame, TblCliError> {
+    match columns {
+        None => Ok(lf),
+        Some(columns) => {
+            let keep_strategy = match keep {
+                UniqueKeep::First => UniqueKeepStrategy::First,
+                UniqueKeep::Last => UniqueKeepStrategy::Last,
+                UniqueKeep::None => UniqueKeepStrategy::None,
+            };
+            let subset = if columns.is_empty() {
+                None
+            } else {
+                Some(columns.to_vec())
+            };
+            Ok(lf.unique_stable(subset, keep_strategy))
+        }
+    }
+}
+
 pub(crate) fn apply_sort(
     lf: LazyFrame,
     raw_columns: Option<&[String]>,
@@ -621,3 +572,67 @@ pub(crate) fn apply_value_counts(lf: LazyFrame, n: Option<&str>) -> Result<LazyF
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duplicated_df() -> DataFrame {
+        df!(
+            "block_number" => [1u32, 1, 2, 1],
+            "value" => [10u32, 10, 20, 30]
+        )
+        .unwrap()
+    }
+
+    fn unique_values(columns: Option<&[String]>, keep: UniqueKeep) -> Vec<Option<u32>> {
+        apply_unique(duplicated_df().lazy(), columns, keep)
+            .unwrap()
+            .collect()
+            .unwrap()
+            .column("value")
+            .unwrap()
+            .u32()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_unique_all_columns() {
+        let all: &[String] = &[];
+        assert_eq!(
+            unique_values(Some(all), UniqueKeep::First),
+            vec![Some(10), Some(20), Some(30)]
+        );
+        assert_eq!(
+            unique_values(Some(all), UniqueKeep::Last),
+            vec![Some(10), Some(20), Some(30)]
+        );
+        assert_eq!(
+            unique_values(Some(all), UniqueKeep::None),
+            vec![Some(20), Some(30)]
+        );
+        assert_eq!(
+            unique_values(None, UniqueKeep::First),
+            vec![Some(10), Some(10), Some(20), Some(30)]
+        );
+    }
+
+    #[test]
+    fn test_apply_unique_subset() {
+        let subset = vec!["block_number".to_string()];
+        assert_eq!(
+            unique_values(Some(&subset), UniqueKeep::First),
+            vec![Some(10), Some(20)]
+        );
+        assert_eq!(
+            unique_values(Some(&subset), UniqueKeep::Last),
+            vec![Some(20), Some(30)]
+        );
+        assert_eq!(
+            unique_values(Some(&subset), UniqueKeep::None),
+            vec![Some(20)]
+        );
+    }
+}
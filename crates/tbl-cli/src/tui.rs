@@ -0,0 +1,504 @@
+use crate::TblCliError;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::{cursor, queue, terminal};
+use polars::prelude::*;
+use std::io::{stdout, IsTerminal, Stdout, Write};
+
+const MAX_COLUMN_WIDTH: usize = 40;
+const COLUMN_GAP: &str = "  ";
+
+/// browse lazyframe in a full-screen terminal viewer
+///
+/// rows are collected one screen at a time, so large inputs are never loaded
+/// into memory all at once. filters stay lazy, so each page scans only until its
+/// rows are found. sorting loads every sorted row into memory once, since each
+/// page would otherwise sort the whole input again
+pub(crate) fn run_viewer(lf: LazyFrame) -> Result<(), TblCliError> {
+    if !stdout().is_terminal() {
+        return Err(TblCliError::Arg(
+            "--tui requires stdout to be a terminal".to_string(),
+        ));
+    }
+    let mut viewer = Viewer::new(lf)?;
+    let _guard = TerminalGuard::enter()?;
+    let mut out = stdout();
+    loop {
+        viewer.render(&mut out)?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if !viewer.handle_key(key, &mut out)? {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// restores terminal state when viewer exits, including on errors
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self, TblCliError> {
+        terminal::enable_raw_mode()?;
+        let mut out = stdout();
+        if let Err(e) = crossterm::execute!(out, terminal::EnterAlternateScreen, cursor::Hide) {
+            let _ = terminal::disable_raw_mode();
+            return Err(e.into());
+        }
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Viewer {
+    base: LazyFrame,
+    lf: LazyFrame,
+    columns: Vec<String>,
+    n_rows: usize,
+    top: usize,
+    column: usize,
+    first_column: usize,
+    sort: Option<String>,
+    filters: Vec<String>,
+    hex: bool,
+    search: Option<String>,
+    message: String,
+    page: Option<(usize, usize, Vec<Vec<String>>)>,
+}
+
+impl Viewer {
+    fn new(lf: LazyFrame) -> Result<Self, TblCliError> {
+        let columns = get_column_names(&lf)?;
+        let n_rows = count_rows(&lf)?;
+        Ok(Viewer {
+            base: lf.clone(),
+            lf,
+            columns,
+            n_rows,
+            top: 0,
+            column: 0,
+            first_column: 0,
+            sort: None,
+            filters: Vec::new(),
+            hex: true,
+            search: None,
+            message: String::new(),
+            page: None,
+        })
+    }
+
+    /// rebuild view from base lazyframe using current sort and filters
+    fn refresh(&mut self) -> Result<(), TblCliError> {
+        let filters = if self.filters.is_empty() {
+            None
+        } else {
+            Some(self.filters.as_slice())
+        };
+        let lf = crate::transform::apply_filter(self.base.clone(), filters)?;
+        let sort = self.sort.clone().map(|sort| vec![sort]);
+        let lf = crate::transform::apply_sort(lf, sort.as_deref())?;
+        // collect sorted view once, rather than sorting again for every page
+        let lf = match self.sort {
+            Some(_) => lf.collect()?.lazy(),
+            None => lf,
+        };
+        let n_rows = count_rows(&lf)?;
+        self.lf = lf;
+        self.n_rows = n_rows;
+        self.top = self.top.min(n_rows.saturating_sub(1));
+        self.page = None;
+        Ok(())
+    }
+
+    /// get rendered cells of visible rows, collecting them only when view moves
+    fn get_page(&mut self, n: usize) -> Result<&[Vec<String>], TblCliError> {
+        let cached = matches!(&self.page, Some((top, len, _)) if *top == self.top && *len == n);
+        if !cached {
+            let mut df = self
+                .lf
+                .clone()
+                .slice(self.top as i64, n as IdxSize)
+                .collect()?;
+            if self.hex {
+                df = crate::output::binary_to_hex(&mut df)?;
+            }
+            let cells = crate::output::get_string_columns(&df)?
+                .into_iter()
+                .map(|values| {
+                    values
+                        .into_iter()
+                        .map(|value| match value {
+                            Some(value) => sanitize(&value),
+                            None => "null".to_string(),
+                        })
+                        .collect()
+                })
+                .collect();
+            self.page = Some((self.top, n, cells));
+        }
+        match &self.page {
+            Some((_, _, cells)) => Ok(cells),
+            None => Ok(&[]),
+        }
+    }
+
+    fn render(&mut self, out: &mut Stdout) -> Result<(), TblCliError> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let n_visible_rows = height.saturating_sub(3).max(1);
+        let cells = self.get_page(n_visible_rows)?.to_vec();
+
+        // compute column widths from header and visible cells
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(c, name)| {
+                let data_width = cells
+                    .get(c)
+                    .map(|values| values.iter().map(|v| v.chars().count()).max().unwrap_or(0))
+                    .unwrap_or(0);
+                data_width.max(name.chars().count()).min(MAX_COLUMN_WIDTH)
+            })
+            .collect();
+
+        // scroll horizontally until cursor column is visible
+        if self.column < self.first_column {
+            self.first_column = self.column;
+        }
+        while self.first_column < self.column
+            && !fits(&widths[self.first_column..=self.column], width)
+        {
+            self.first_column += 1;
+        }
+        let mut visible = Vec::new();
+        let mut used = 0;
+        for (c, column_width) in widths.iter().enumerate().skip(self.first_column) {
+            if !visible.is_empty() && used + COLUMN_GAP.len() + column_width > width {
+                break;
+            }
+            if !visible.is_empty() {
+                used += COLUMN_GAP.len();
+            }
+            used += column_width;
+            visible.push(c);
+        }
+
+        queue!(
+            out,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+
+        // header and separator
+        for (i, c) in visible.iter().enumerate() {
+            if i > 0 {
+                queue!(out, Print(COLUMN_GAP))?;
+            }
+            let text = pad(&self.columns[*c], widths[*c]);
+            queue!(out, SetAttribute(Attribute::Bold))?;
+            if *c == self.column {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(out, Print(text), SetAttribute(Attribute::Reset))?;
+        }
+        queue!(out, cursor::MoveTo(0, 1))?;
+        let separator: Vec<String> = visible.iter().map(|c| "─".repeat(widths[*c])).collect();
+        queue!(out, Print(clip(&separator.join(COLUMN_GAP), width)))?;
+
+        // rows
+        let n_page_rows = cells.first().map(|values| values.len()).unwrap_or(0);
+        for (y, r) in (2..).zip(0..n_page_rows) {
+            queue!(out, cursor::MoveTo(0, y))?;
+            for (i, c) in visible.iter().enumerate() {
+                if i > 0 {
+                    queue!(out, Print(COLUMN_GAP))?;
+                }
+                let text = pad(&cells[*c][r], widths[*c]);
+                if *c == self.column {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Underlined),
+                        Print(text),
+                        SetAttribute(Attribute::Reset)
+                    )?;
+                } else {
+                    queue!(out, Print(text))?;
+                }
+            }
+        }
+
+        // status line
+        let last_row = (self.top + n_page_rows).min(self.n_rows);
+        let mut status = format!(
+            " rows {}-{} of {} | column {} of {}",
+            if n_page_rows == 0 { 0 } else { self.top + 1 },
+            last_row,
+            self.n_rows,
+            self.column + 1,
+            self.columns.len(),
+        );
+        if let Some(sort) = &self.sort {
+            status.push_str(&format!(" | sort {}", sort));
+        }
+        if !self.filters.is_empty() {
+            status.push_str(&format!(" | filter {}", self.filters.join(", ")));
+        }
+        if !self.message.is_empty() {
+            status.push_str(&format!(" | {}", self.message));
+        }
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(pad(&clip(&status, width), width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// handle key press, returning false when viewer should exit
+    fn handle_key(&mut self, key: KeyEvent, out: &mut Stdout) -> Result<bool, TblCliError> {
+        let (_, height) = terminal::size()?;
+        let page = (height as usize).saturating_sub(3).max(1);
+        let last_top = self.n_rows.saturating_sub(1);
+        self.message.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(false)
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.top = (self.top + 1).min(last_top),
+            KeyCode::Char('k') | KeyCode::Up => self.top = self.top.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.top = (self.top + page).min(last_top),
+            KeyCode::PageUp | KeyCode::Char('b') => self.top = self.top.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => self.top = 0,
+            KeyCode::Char('G') | KeyCode::End => self.top = self.n_rows.saturating_sub(page),
+            KeyCode::Char('l') | KeyCode::Right => {
+                self.column = (self.column + 1).min(self.columns.len().saturating_sub(1))
+            }
+            KeyCode::Char('h') | KeyCode::Left => self.column = self.column.saturating_sub(1),
+            KeyCode::Char('x') => {
+                self.hex = !self.hex;
+                self.page = None;
+                self.message = format!("hex {}", if self.hex { "on" } else { "off" });
+            }
+            KeyCode::Char('/') => {
+                if let Some(query) = prompt(out, "column search: ")? {
+                    self.search = Some(query);
+                    self.find_column();
+                }
+            }
+            KeyCode::Char('n') => self.find_column(),
+            KeyCode::Char('s') | KeyCode::Char('S') if self.columns.is_empty() => {
+                self.message = "no columns to sort".to_string();
+            }
+            KeyCode::Char('s') | KeyCode::Char('S') => {
+                let previous = self.sort.clone();
+                let name = self.columns[self.column].clone();
+                self.sort = if key.code == KeyCode::Char('S') {
+                    Some(format!("{}:desc", name))
+                } else {
+                    Some(name)
+                };
+                self.top = 0;
+                if let Err(e) = self.refresh() {
+                    self.sort = previous;
+                    self.message = e.to_string();
+                    self.refresh()?;
+                }
+            }
+            KeyCode::Char('f') => {
+                if let Some(filter) = prompt(out, "filter: ")? {
+                    self.filters.push(filter);
+                    self.top = 0;
+                    if let Err(e) = self.refresh() {
+                        self.filters.pop();
+                        self.message = e.to_string();
+                        self.refresh()?;
+                    }
+                }
+            }
+            KeyCode::Char('r') => {
+                self.sort = None;
+                self.filters.clear();
+                self.top = 0;
+                self.refresh()?;
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// move cursor to next column whose name contains the search query
+    fn find_column(&mut self) {
+        let query = match &self.search {
+            Some(query) => query.to_lowercase(),
+            None => return,
+        };
+        let n = self.columns.len();
+        let found = (1..=n)
+            .map(|offset| (self.column + offset) % n)
+            .find(|c| self.columns[*c].to_lowercase().contains(&query));
+        match found {
+            Some(c) => self.column = c,
+            None => self.message = format!("no column matches {:?}", query),
+        }
+    }
+}
+
+/// read a line of input on the status line, returning None if cancelled
+fn prompt(out: &mut Stdout, label: &str) -> Result<Option<String>, TblCliError> {
+    let (width, height) = terminal::size()?;
+    let mut input = String::new();
+    loop {
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            Print(clip(&format!("{}{}", label, input), width as usize))
+        )?;
+        out.flush()?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Enter if input.is_empty() => return Ok(None),
+                KeyCode::Enter => return Ok(Some(input)),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn get_column_names(lf: &LazyFrame) -> Result<Vec<String>, TblCliError> {
+    Ok(lf
+        .clone()
+        .schema()?
+        .iter_names()
+        .map(|name| name.to_string())
+        .collect())
+}
+
+fn count_rows(lf: &LazyFrame) -> Result<usize, TblCliError> {
+    Ok(lf
+        .clone()
+        .select([len()])
+        .with_streaming(true)
+        .collect()?
+        .column("len")?
+        .cast(&DataType::UInt64)?
+        .u64()?
+        .get(0)
+        .unwrap_or(0) as usize)
+}
+
+/// whether columns of given widths fit on one line
+fn fits(widths: &[usize], width: usize) -> bool {
+    let total: usize =
+        widths.iter().sum::<usize>() + COLUMN_GAP.len() * widths.len().saturating_sub(1);
+    total <= width
+}
+
+/// replace control characters so cells cannot move the cursor
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { '·' } else { c })
+        .collect()
+}
+
+/// truncate text to width, marking truncation with an ellipsis
+fn clip(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        let mut clipped: String = text.chars().take(width - 1).collect();
+        clipped.push('…');
+        clipped
+    }
+}
+
+/// truncate or pad text to exactly width characters
+fn pad(text: &str, width: usize) -> String {
+    let clipped = clip(text, width);
+    let n = clipped.chars().count();
+    format!("{}{}", clipped, " ".repeat(width - n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip() {
+        assert_eq!(clip("block_number", 20), "block_number");
+        assert_eq!(clip("block_number", 12), "block_number");
+        assert_eq!(clip("block_number", 6), "block…");
+        assert_eq!(clip("block_number", 1), "…");
+        assert_eq!(clip("block_number", 0), "");
+        assert_eq!(clip("ñññ", 2), "ñ…");
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad("abc", 5), "abc  ");
+        assert_eq!(pad("abcdef", 4), "abc…");
+        assert_eq!(pad("ñ", 3), "ñ  ");
+        assert_eq!(pad("", 0), "");
+    }
+
+    #[test]
+    fn test_fits() {
+        assert!(fits(&[], 0));
+        assert!(fits(&[5], 5));
+        assert!(!fits(&[6], 5));
+        assert!(fits(&[3, 4], 3 + COLUMN_GAP.len() + 4));
+        assert!(!fits(&[3, 4], 3 + COLUMN_GAP.len() + 3));
+    }
+
+    #[test]
+    fn test_find_column() {
+        let df = df!(
+            "block_number" => [1u32],
+            "tx_hash" => ["a"],
+            "from_address" => ["b"],
+            "to_address" => ["c"]
+        )
+        .unwrap();
+        let mut viewer = Viewer::new(df.lazy()).unwrap();
+        assert_eq!(viewer.n_rows, 1);
+
+        // searches wrap around and start after the cursor column
+        viewer.search = Some("ADDRESS".to_string());
+        viewer.find_column();
+        assert_eq!(viewer.column, 2);
+        viewer.find_column();
+        assert_eq!(viewer.column, 3);
+        viewer.find_column();
+        assert_eq!(viewer.column, 2);
+
+        viewer.search = Some("missing".to_string());
+        viewer.find_column();
+        assert_eq!(viewer.column, 2);
+        assert!(viewer.message.contains("no column matches"));
+    }
+}
@@ -1,4 +1,6 @@
 use tbl_core::TblError;
+
+pub(crate) use tbl_core::writers::OutputFileFormat;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -48,6 +50,7 @@ pub(crate) enum OutputMode {
     Partition,
     InteractiveLf,
     InteractiveDf,
+    Tui,
 }
 
 impl OutputMode {
@@ -60,4 +63,17 @@ impl OutputMode {
                 | OutputMode::Partition
         )
     }
+
+    /// whether all inputs go into one output, so inputs without matching rows can be skipped
+    pub(crate) fn merges_inputs(&self) -> bool {
+        matches!(
+            self,
+            OutputMode::PrintToStdout
+                | OutputMode::SaveToSingleFile
+                | OutputMode::Partition
+                | OutputMode::InteractiveLf
+                | OutputMode::InteractiveDf
+                | OutputMode::Tui
+        )
+    }
 }
@@ -1,5 +1,6 @@
 use crate::TblError;
 use futures::stream::StreamExt;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 /// return tabular file paths within directory
@@ -19,19 +20,34 @@ pub fn get_directory_tabular_files(dir_path: &Path) -> Result<Vec<PathBuf>, TblE
 }
 
 /// get tabular files inside directory tree
+///
+/// symbolic links are followed, but each directory is only visited once so
+/// that links pointing back up the tree do not recurse forever
 pub fn get_tree_tabular_files(dir_path: &std::path::Path) -> Result<Vec<PathBuf>, TblError> {
     let mut tabular_files = Vec::new();
+    let mut visited = HashSet::new();
+    gather_tree_tabular_files(dir_path, &mut visited, &mut tabular_files)?;
+    Ok(tabular_files)
+}
+
+fn gather_tree_tabular_files(
+    dir_path: &Path,
+    visited: &mut HashSet<PathBuf>,
+    tabular_files: &mut Vec<PathBuf>,
+) -> Result<(), TblError> {
+    if !visited.insert(std::fs::canonicalize(dir_path)?) {
+        return Ok(());
+    }
     for entry in std::fs::read_dir(dir_path)? {
         let entry = entry?;
         let path = entry.path();
         if path.is_file() && is_tabular_file(&path) {
             tabular_files.push(path);
         } else if path.is_dir() {
-            let sub_dir_files = get_tree_tabular_files(&path)?;
-            tabular_files.extend(sub_dir_files);
+            gather_tree_tabular_files(&path, visited, tabular_files)?;
         }
     }
-    Ok(tabular_files)
+    Ok(())
 }
 
 /// return true if file_path has a tabular extension
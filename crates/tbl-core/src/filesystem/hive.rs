@@ -0,0 +1,244 @@
+use crate::filters::{ColumnFilter, FilterOperator};
+use std::path::{Path, PathBuf};
+
+/// directory value of hive partitions whose value is null
+pub const HIVE_NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// get input root that path was selected from, the deepest root containing it
+pub fn get_hive_root<'a>(path: &Path, roots: &'a [PathBuf]) -> Option<&'a Path> {
+    roots
+        .iter()
+        .filter(|root| strip_hive_root(path, root).is_some())
+        .max_by_key(|root| root.components().count())
+        .map(|root| root.as_path())
+}
+
+fn strip_hive_root<'a>(path: &'a Path, root: &Path) -> Option<&'a Path> {
+    let path = path.strip_prefix(".").unwrap_or(path);
+    let root = root.strip_prefix(".").unwrap_or(root);
+    path.strip_prefix(root).ok()
+}
+
+/// get `key=value` directory segments of path below its input root
+fn get_hive_segments<'a>(path: &'a Path, roots: &[PathBuf]) -> Vec<&'a str> {
+    let relative = match get_hive_root(path, roots) {
+        Some(root) => strip_hive_root(path, root),
+        None => None,
+    };
+    let parent = match relative.and_then(|relative| relative.parent()) {
+        Some(parent) => parent,
+        None => return Vec::new(),
+    };
+    parent
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .filter(|segment| split_hive_segment(segment).is_some())
+        .collect()
+}
+
+/// split directory name into hive key and raw value, following polars
+///
+/// names with more than one `=` or with a `*` in the value are not partitions
+pub fn split_hive_segment(segment: &str) -> Option<(&str, &str)> {
+    let (key, value) = segment.split_once('=')?;
+    if value.contains(['=', '*']) {
+        None
+    } else {
+        Some((key, value))
+    }
+}
+
+/// parse hive partitions from `key=value` directories of path below its input root
+///
+/// `roots` are the directories that paths were selected from, so directories
+/// above them are never partitions, and files given directly have none. the
+/// file name itself is never a partition. values are percent-decoded, and empty
+/// values or `__HIVE_DEFAULT_PARTITION__` are null
+pub fn parse_hive_partitions(path: &Path, roots: &[PathBuf]) -> Vec<(String, Option<String>)> {
+    get_hive_segments(path, roots)
+        .into_iter()
+        .filter_map(split_hive_segment)
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| {
+            let value = match value {
+                "" | HIVE_NULL_PARTITION => None,
+                value => Some(unescape_hive_value(value)),
+            };
+            (key.to_string(), value)
+        })
+        .collect()
+}
+
+/// get names of hive partition columns across paths, in order of first appearance
+pub fn get_hive_columns(paths: &[PathBuf], roots: &[PathBuf]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for path in paths.iter() {
+        for (key, _) in parse_hive_partitions(path, roots).into_iter() {
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+    columns
+}
+
+/// get relative directory of hive partition segments of path below its input root
+pub fn get_hive_dir(path: &Path, roots: &[PathBuf]) -> PathBuf {
+    get_hive_segments(path, roots).into_iter().collect()
+}
+
+/// get directory of hive partition below root
+///
+/// values are escaped so that they read back unchanged, and null values are
+/// written as `__HIVE_DEFAULT_PARTITION__`
+pub fn get_hive_partition_path(root: &Path, partitions: &[(String, Option<String>)]) -> PathBuf {
+    let mut path = root.to_path_buf();
+    for (key, value) in partitions.iter() {
+        let value = match value.as_deref() {
+            Some("") | None => HIVE_NULL_PARTITION.to_string(),
+            Some(value) => escape_hive_value(value),
+        };
+        path.push(format!("{}={}", key, value));
+    }
+    path
+}
+
+/// percent-encode characters that cannot appear in a hive directory value
+pub fn escape_hive_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' | '/' | '\\' | '=' | '*' => escaped.push_str(&format!("%{:02X}", c as u32)),
+            c if c.is_ascii_control() => escaped.push_str(&format!("%{:02X}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// decode percent-encoded bytes of a hive directory value
+fn unescape_hive_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| value.to_string())
+}
+
+/// keep only paths whose hive partition values pass the filters
+///
+/// filters on columns that are not partitions of a path are ignored, so files
+/// are only skipped when their directories alone rule them out
+pub fn prune_hive_paths(
+    paths: Vec<PathBuf>,
+    roots: &[PathBuf],
+    filters: &[ColumnFilter],
+) -> Vec<PathBuf> {
+    if filters.is_empty() {
+        return paths;
+    }
+    paths
+        .into_iter()
+        .filter(|path| {
+            let partitions = parse_hive_partitions(path, roots);
+            filters.iter().all(|filter| {
+                match partitions.iter().find(|(key, _)| key == &filter.column) {
+                    Some((_, Some(value))) => filter.matches_str(value),
+                    Some((_, None)) => filter.operator == FilterOperator::IsNull,
+                    None => true,
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::parse_column_filter;
+
+    #[test]
+    fn test_parse_hive_partitions() {
+        let roots = vec![PathBuf::from("/mnt/env=prod/data")];
+        let path = Path::new("/mnt/env=prod/data/chain=1/year=2024/part-0.parquet");
+        assert_eq!(
+            parse_hive_partitions(path, &roots),
+            vec![
+                ("chain".to_string(), Some("1".to_string())),
+                ("year".to_string(), Some("2024".to_string()))
+            ]
+        );
+        assert_eq!(
+            get_hive_dir(path, &roots),
+            PathBuf::from("chain=1/year=2024")
+        );
+        assert!(parse_hive_partitions(path, &[]).is_empty());
+        assert!(parse_hive_partitions(path, &[path.to_path_buf()]).is_empty());
+        let path = Path::new("/mnt/env=prod/data/a=1.parquet");
+        assert!(parse_hive_partitions(path, &roots).is_empty());
+
+        // relative roots and paths
+        let roots = vec![PathBuf::from("./data")];
+        let path = Path::new("data/x=__HIVE_DEFAULT_PARTITION__/y=a%2Fb%3Dc/part-0.parquet");
+        assert_eq!(
+            parse_hive_partitions(path, &roots),
+            vec![
+                ("x".to_string(), None),
+                ("y".to_string(), Some("a/b=c".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_hive_partition_path() {
+        let partitions = vec![
+            ("x".to_string(), None),
+            ("y".to_string(), Some("a/b=c%".to_string())),
+        ];
+        let path = get_hive_partition_path(Path::new("/out"), &partitions);
+        assert_eq!(
+            path,
+            PathBuf::from("/out/x=__HIVE_DEFAULT_PARTITION__/y=a%2Fb%3Dc%25")
+        );
+        let roots = vec![PathBuf::from("/out")];
+        assert_eq!(
+            parse_hive_partitions(&path.join("part-0.parquet"), &roots),
+            partitions
+        );
+    }
+
+    #[test]
+    fn test_prune_hive_paths() {
+        let roots = vec![PathBuf::from("/data")];
+        let paths = vec![
+            PathBuf::from("/data/year=2023/part-0.parquet"),
+            PathBuf::from("/data/year=2024/part-0.parquet"),
+            PathBuf::from("/data/year=__HIVE_DEFAULT_PARTITION__/part-0.parquet"),
+            PathBuf::from("/data/other/part-0.parquet"),
+        ];
+        let filters = vec![parse_column_filter("year>=2024").unwrap()];
+        assert_eq!(
+            prune_hive_paths(paths, &roots, &filters),
+            vec![
+                PathBuf::from("/data/year=2024/part-0.parquet"),
+                PathBuf::from("/data/other/part-0.parquet"),
+            ]
+        );
+    }
+}
@@ -1,7 +1,107 @@
 use crate::TblError;
-use std::path::PathBuf;
+use glob::{MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// input path spec
+#[derive(Debug, Clone)]
+pub struct InputPathSpec {
+    /// input files, directories, or glob patterns
+    pub inputs: Option<Vec<PathBuf>>,
+    /// tree
+    pub tree: bool,
+    /// sort
+    pub sort: bool,
+    /// glob patterns of paths to exclude
+    pub exclude: Vec<String>,
+    /// maximum depth of directories to descend, 1 for only files directly inside
+    pub max_depth: Option<usize>,
+    /// follow symbolic links
+    pub follow_symlinks: bool,
+    /// include hidden files and directories
+    pub hidden: bool,
+    /// input paths taken literally rather than as globs, e.g. from `read_path_list`
+    pub listed_paths: Option<Vec<PathBuf>>,
+}
+
+impl Default for InputPathSpec {
+    fn default() -> Self {
+        InputPathSpec {
+            inputs: None,
+            tree: false,
+            sort: false,
+            exclude: Vec::new(),
+            max_depth: None,
+            follow_symlinks: true,
+            hidden: true,
+            listed_paths: None,
+        }
+    }
+}
+
+impl InputPathSpec {
+    /// create new InputPathSpec
+    pub fn new() -> Self {
+        InputPathSpec::default()
+    }
+
+    /// set inputs
+    pub fn inputs<I>(mut self, inputs: I) -> Self
+    where
+        I: Into<super::outputs::InputPaths>,
+    {
+        self.inputs = inputs.into().0;
+        self
+    }
+
+    /// set tree
+    pub fn tree(mut self, tree: bool) -> Self {
+        self.tree = tree;
+        self
+    }
+
+    /// set sort
+    pub fn sort(mut self, sort: bool) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// set exclude patterns
+    pub fn exclude(mut self, exclude: Vec<String>) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    /// set max_depth
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// set follow_symlinks
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// set hidden
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// set listed_paths
+    pub fn listed_paths(mut self, listed_paths: Option<Vec<PathBuf>>) -> Self {
+        self.listed_paths = listed_paths;
+        self
+    }
+}
 
 /// get file paths
+///
+/// inputs are local files and directories, use `get_input_paths_async` for
+/// remote paths and glob patterns
 pub fn get_input_paths(
     inputs: &Option<Vec<PathBuf>>,
     tree: bool,
@@ -37,3 +137,433 @@ pub fn get_input_paths(
 
     Ok(paths)
 }
+
+/// get file paths
+///
+/// inputs may be local paths, glob patterns, or remote `s3://bucket/prefix` paths
+pub async fn get_input_paths_async(
+    inputs: &Option<Vec<PathBuf>>,
+    tree: bool,
+    sort: bool,
+) -> Result<Vec<PathBuf>, TblError> {
+    let spec = InputPathSpec::new()
+        .inputs(inputs.clone())
+        .tree(tree)
+        .sort(sort)
+        .hidden(true);
+    select_input_paths(&spec).await
+}
+
+/// get file paths selected by input path spec
+///
+/// inputs can be files, directories, remote paths, or glob patterns such as
+/// `data/**/transactions__*.parquet`. exclude patterns that contain a `/` are
+/// matched against whole paths, other patterns against file and directory names.
+pub async fn select_input_paths(spec: &InputPathSpec) -> Result<Vec<PathBuf>, TblError> {
+    // get paths, listed paths are taken literally rather than as globs
+    let listed_paths = spec.listed_paths.clone().unwrap_or_default();
+    let raw_paths = match &spec.inputs {
+        Some(raw_paths) => raw_paths.to_vec(),
+        None if spec.listed_paths.is_some() => Vec::new(),
+        None => vec![std::env::current_dir()?],
+    };
+    let n_globbable = raw_paths.len();
+    let raw_paths = raw_paths.into_iter().chain(listed_paths);
+    let exclude = spec
+        .exclude
+        .iter()
+        .map(|pattern| compile_pattern(pattern))
+        .collect::<Result<Vec<Pattern>, TblError>>()?;
+    let max_depth = match spec.max_depth {
+        Some(max_depth) => Some(max_depth),
+        None if spec.tree => None,
+        None => Some(1),
+    };
+
+    // expand globs and directories
+    let mut paths: Vec<PathBuf> = vec![];
+    let mut visited = HashSet::new();
+    for (index, raw_path) in raw_paths.enumerate() {
+        if index < n_globbable && is_glob_pattern(&raw_path) {
+            paths.extend(expand_glob_pattern(&raw_path, spec, &exclude).await?);
+        } else if super::remote::is_remote_path(&raw_path) {
+            if super::gather::is_tabular_file(&raw_path) {
+                paths.push(raw_path);
+            } else {
+                let remote_tree = max_depth.map(|depth| depth > 1).unwrap_or(true);
+                let sub_paths =
+                    super::remote::get_remote_tabular_files(&raw_path, remote_tree).await?;
+                paths.extend(
+                    sub_paths
+                        .into_iter()
+                        .filter(|path| !is_excluded(path, &exclude)),
+                );
+            }
+        } else if raw_path.is_dir() {
+            walk_tabular_files(
+                &raw_path,
+                max_depth,
+                spec,
+                &exclude,
+                &mut visited,
+                &mut paths,
+            )?;
+        } else if super::gather::is_tabular_file(&raw_path) {
+            if !is_excluded(&raw_path, &exclude) {
+                paths.push(raw_path);
+            }
+        } else {
+            println!("skipping non-tabular file {:?}", raw_path)
+        }
+    }
+
+    // sort
+    if spec.sort {
+        paths.sort();
+        paths.dedup();
+    }
+
+    Ok(paths)
+}
+
+/// get directories that hive partitions of selected paths are parsed relative to
+///
+/// these are the input directories, remote prefixes, and literal bases of glob
+/// patterns. files given directly or listed by path have no partitions
+pub fn get_hive_roots(spec: &InputPathSpec) -> Result<Vec<PathBuf>, TblError> {
+    let raw_paths = match &spec.inputs {
+        Some(raw_paths) => raw_paths.to_vec(),
+        None if spec.listed_paths.is_some() => Vec::new(),
+        None => vec![std::env::current_dir()?],
+    };
+    let mut roots = Vec::new();
+    for raw_path in raw_paths.into_iter() {
+        if is_glob_pattern(&raw_path) {
+            let (base, _) = split_glob_base(&raw_path.to_string_lossy());
+            roots.push(PathBuf::from(base));
+        } else if super::remote::is_remote_path(&raw_path) {
+            if !super::gather::is_tabular_file(&raw_path) {
+                roots.push(raw_path);
+            }
+        } else if raw_path.is_dir() {
+            roots.push(raw_path);
+        }
+    }
+    Ok(roots)
+}
+
+/// read list of paths from file, or from stdin if path is `-`
+///
+/// paths are separated by NUL bytes if any are present, otherwise by newlines
+pub fn read_path_list(path: &Path) -> Result<Vec<PathBuf>, TblError> {
+    let mut contents = Vec::new();
+    if path == Path::new("-") {
+        std::io::stdin().read_to_end(&mut contents)?;
+    } else {
+        contents = std::fs::read(path)?;
+    }
+    Ok(parse_path_list(&contents))
+}
+
+fn parse_path_list(contents: &[u8]) -> Vec<PathBuf> {
+    let separator = if contents.contains(&0) { 0 } else { b'\n' };
+    contents
+        .split(|byte| *byte == separator)
+        .map(|entry| match (separator, entry) {
+            (b'\n', [entry @ .., b'\r']) => entry,
+            _ => entry,
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(path_from_bytes)
+        .collect()
+}
+
+/// build path from raw bytes, keeping paths that are not valid utf-8 intact on unix
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).to_string())
+}
+
+/// return true if path contains glob characters
+pub fn is_glob_pattern(path: &Path) -> bool {
+    path.to_str()
+        .map(|path| path.contains(['*', '?', '[']))
+        .unwrap_or(false)
+}
+
+fn compile_pattern(pattern: &str) -> Result<Pattern, TblError> {
+    Pattern::new(pattern)
+        .map_err(|e| TblError::InputError(format!("invalid glob pattern {}: {}", pattern, e)))
+}
+
+fn match_options(hidden: bool) -> MatchOptions {
+    MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: !hidden,
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.starts_with('.'))
+        .unwrap_or(false)
+}
+
+fn is_excluded(path: &Path, exclude: &[Pattern]) -> bool {
+    let options = match_options(true);
+    exclude.iter().any(|pattern| {
+        if pattern.as_str().contains('/') {
+            pattern.matches_path_with(path, options)
+                || path
+                    .strip_prefix(".")
+                    .map(|path| pattern.matches_path_with(path, options))
+                    .unwrap_or(false)
+        } else {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| pattern.matches_with(name, options))
+                .unwrap_or(false)
+        }
+    })
+}
+
+/// walk directory collecting tabular files, descending at most `depth` levels
+fn walk_tabular_files(
+    dir: &Path,
+    depth: Option<usize>,
+    spec: &InputPathSpec,
+    exclude: &[Pattern],
+    visited: &mut HashSet<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), TblError> {
+    if depth == Some(0) {
+        return Ok(());
+    }
+
+    // avoid cycles through symbolic links
+    if spec.follow_symlinks && !visited.insert(std::fs::canonicalize(dir)?) {
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if (!spec.hidden && is_hidden(&path)) || is_excluded(&path, exclude) {
+            continue;
+        }
+
+        let mut file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            if !spec.follow_symlinks {
+                continue;
+            }
+            file_type = match std::fs::metadata(&path) {
+                Ok(metadata) => metadata.file_type(),
+                Err(_) => continue,
+            };
+        }
+
+        if file_type.is_dir() {
+            let sub_depth = depth.map(|depth| depth - 1);
+            walk_tabular_files(&path, sub_depth, spec, exclude, visited, paths)?;
+        } else if file_type.is_file() && super::gather::is_tabular_file(&path) {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// split glob pattern into literal base directory and the number of pattern
+/// segments below it, or none if the pattern can match at any depth
+fn split_glob_base(pattern: &str) -> (String, Option<usize>) {
+    let segments: Vec<&str> = pattern.split('/').collect();
+    let n_literal = segments
+        .iter()
+        .position(|segment| segment.contains(['*', '?', '[']))
+        .unwrap_or(segments.len());
+    let base = segments[..n_literal].join("/");
+    let base = if base.is_empty() && pattern.starts_with('/') {
+        "/".to_string()
+    } else {
+        base
+    };
+    let rest = &segments[n_literal..];
+    let depth = if rest.contains(&"**") {
+        None
+    } else {
+        Some(rest.len())
+    };
+    (base, depth)
+}
+
+async fn expand_glob_pattern(
+    raw_path: &Path,
+    spec: &InputPathSpec,
+    exclude: &[Pattern],
+) -> Result<Vec<PathBuf>, TblError> {
+    let pattern_str = raw_path
+        .to_str()
+        .ok_or_else(|| TblError::InputError(format!("invalid glob pattern {:?}", raw_path)))?;
+    let pattern = compile_pattern(pattern_str)?;
+    let options = match_options(spec.hidden);
+    let (base, depth) = split_glob_base(pattern_str);
+    let depth = match (depth, spec.max_depth) {
+        (Some(depth), _) => Some(depth),
+        (None, max_depth) => max_depth,
+    };
+
+    // gather candidates below the literal base
+    let mut candidates = Vec::new();
+    if super::remote::is_remote_path(raw_path) {
+        let base = PathBuf::from(base);
+        candidates = super::remote::get_remote_tabular_files(&base, true).await?;
+    } else if base.is_empty() {
+        let mut visited = HashSet::new();
+        walk_tabular_files(
+            Path::new("."),
+            depth,
+            spec,
+            exclude,
+            &mut visited,
+            &mut candidates,
+        )?;
+        candidates = candidates
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(".")
+                    .map(|p| p.to_path_buf())
+                    .unwrap_or(path)
+            })
+            .collect();
+    } else if Path::new(&base).is_dir() {
+        let mut visited = HashSet::new();
+        walk_tabular_files(
+            Path::new(&base),
+            depth,
+            spec,
+            exclude,
+            &mut visited,
+            &mut candidates,
+        )?;
+    }
+
+    Ok(candidates
+        .into_iter()
+        .filter(|path| pattern.matches_path_with(path, options))
+        .filter(|path| !is_excluded(path, exclude))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use tempfile::TempDir;
+
+    fn create_test_file_tree() -> Result<TempDir, TblError> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("2024-05/sub"))?;
+        fs::create_dir_all(root.join("2024-06"))?;
+        fs::create_dir_all(root.join(".hidden"))?;
+        File::create(root.join("transactions__1.parquet"))?;
+        File::create(root.join("blocks__1.parquet"))?;
+        File::create(root.join("2024-05/transactions__2.parquet"))?;
+        File::create(root.join("2024-05/sub/transactions__3.parquet"))?;
+        File::create(root.join("2024-06/transactions__4.parquet"))?;
+        File::create(root.join(".hidden/transactions__5.parquet"))?;
+        Ok(temp_dir)
+    }
+
+    fn names(paths: &[PathBuf]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_path_list() {
+        let expected = vec![PathBuf::from("a.parquet"), PathBuf::from("dir/b c.parquet")];
+        assert_eq!(parse_path_list(b"a.parquet\ndir/b c.parquet\n"), expected);
+        assert_eq!(parse_path_list(b"a.parquet\r\n\ndir/b c.parquet"), expected);
+        assert_eq!(parse_path_list(b"a.parquet\0dir/b c.parquet\0"), expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_non_utf8_path_list() {
+        use std::os::unix::ffi::OsStrExt;
+        let paths = parse_path_list(b"caf\xe9.parquet\n");
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].as_os_str().as_bytes(), b"caf\xe9.parquet");
+    }
+
+    #[tokio::test]
+    async fn test_select_input_paths() -> Result<(), TblError> {
+        let temp_dir = create_test_file_tree()?;
+        let root = temp_dir.path().to_path_buf();
+
+        // glob across tree, excluding a directory
+        let pattern = root.join("**/transactions__*.parquet");
+        let spec = InputPathSpec::new()
+            .inputs(vec![pattern])
+            .exclude(vec!["2024-06".to_string()])
+            .hidden(false)
+            .sort(true);
+        let paths = select_input_paths(&spec).await?;
+        assert_eq!(
+            names(&paths),
+            vec![
+                "transactions__3.parquet",
+                "transactions__2.parquet",
+                "transactions__1.parquet"
+            ]
+        );
+
+        // limited depth
+        let spec = InputPathSpec::new()
+            .inputs(vec![root.clone()])
+            .tree(true)
+            .max_depth(Some(2))
+            .hidden(false)
+            .sort(true);
+        let paths = select_input_paths(&spec).await?;
+        assert_eq!(paths.len(), 4);
+
+        // hidden files are included unless skipped
+        let spec = InputPathSpec::new().inputs(vec![root.clone()]).tree(true);
+        let paths = select_input_paths(&spec).await?;
+        assert_eq!(paths.len(), 6);
+        let spec = spec.hidden(false);
+        let paths = select_input_paths(&spec).await?;
+        assert_eq!(paths.len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_hive_roots() -> Result<(), TblError> {
+        let temp_dir = create_test_file_tree()?;
+        let root = temp_dir.path().to_path_buf();
+        let spec = InputPathSpec::new().inputs(vec![
+            root.join("2024-05"),
+            root.join("blocks__1.parquet"),
+            root.join("2024-06/*.parquet"),
+        ]);
+        assert_eq!(
+            get_hive_roots(&spec)?,
+            vec![root.join("2024-05"), root.join("2024-06")]
+        );
+        Ok(())
+    }
+}
@@ -33,14 +33,17 @@ pub fn convert_file_path(
     output_dir: &Option<PathBuf>,
     file_prefix: &Option<String>,
     file_postfix: &Option<String>,
+    hive_roots: &[PathBuf],
 ) -> Result<PathBuf, TblError> {
-    // change output directory
+    // change output directory, keeping hive partition directories of input
     let output = match output_dir.as_ref() {
         Some(output_dir) => {
             let file_name = input
                 .file_name()
                 .ok_or_else(|| TblError::Error("Invalid input path".to_string()))?;
-            output_dir.join(file_name)
+            output_dir
+                .join(super::hive::get_hive_dir(input, hive_roots))
+                .join(file_name)
         }
         None => input.to_path_buf(),
     };
@@ -17,3 +17,15 @@ pub use outputs::*;
 /// path size
 pub mod sizes;
 pub use sizes::*;
+
+/// path range functions
+pub mod ranges;
+pub use ranges::*;
+
+/// remote object storage functions
+pub mod remote;
+pub use remote::*;
+
+/// hive partition functions
+pub mod hive;
+pub use hive::*;
@@ -17,6 +17,8 @@ pub struct OutputPathSpec {
     pub file_postfix: Option<String>,
     /// sort
     pub sort: bool,
+    /// directories below which hive partition directories of inputs are kept
+    pub hive_roots: Vec<PathBuf>,
 }
 
 impl OutputPathSpec {
@@ -72,6 +74,12 @@ impl OutputPathSpec {
         self.sort = sort;
         self
     }
+
+    /// set hive_roots
+    pub fn hive_roots(mut self, hive_roots: Vec<PathBuf>) -> Self {
+        self.hive_roots = hive_roots;
+        self
+    }
 }
 
 /// output dir type
@@ -130,7 +138,7 @@ where
 
 // New wrapper type
 /// InputPaths
-pub struct InputPaths(Option<Vec<PathBuf>>);
+pub struct InputPaths(pub(crate) Option<Vec<PathBuf>>);
 
 impl From<Vec<PathBuf>> for InputPaths {
     fn from(v: Vec<PathBuf>) -> Self {
@@ -225,18 +233,20 @@ pub fn get_output_paths(
     let mut return_inputs: Vec<PathBuf> = Vec::new();
     let mut return_outputs: Vec<PathBuf> = Vec::new();
     for input in inputs {
-        let metadata = std::fs::metadata(&input)?;
-        if metadata.is_file() {
+        // remote inputs are files, remote prefixes must be expanded beforehand
+        let is_file = super::remote::is_remote_path(&input) || std::fs::metadata(&input)?.is_file();
+        if is_file {
             // case 1: input is a file
             let output = super::manipulate::convert_file_path(
                 &input,
                 &output_dir,
                 &output_spec.file_prefix,
                 &output_spec.file_postfix,
+                &output_spec.hive_roots,
             )?;
             return_inputs.push(input.clone());
             return_outputs.push(output);
-        } else if metadata.is_dir() {
+        } else if input.is_dir() {
             if !output_spec.tree {
                 // case 2: input is a directory, non-tree mode
                 for sub_input in super::gather::get_directory_tabular_files(&input)?.into_iter() {
@@ -245,6 +255,7 @@ pub fn get_output_paths(
                         &output_dir,
                         &output_spec.file_prefix,
                         &output_spec.file_postfix,
+                        &output_spec.hive_roots,
                     )?;
                     return_inputs.push(sub_input);
                     return_outputs.push(output);
@@ -266,6 +277,7 @@ pub fn get_output_paths(
                         &None,
                         &output_spec.file_prefix,
                         &output_spec.file_postfix,
+                        &output_spec.hive_roots,
                     )?;
 
                     return_inputs.push(sub_input.clone());
@@ -682,4 +694,16 @@ mod tests {
         },
 
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tree_with_symlink_cycle() -> Result<(), TblError> {
+        let temp_dir = create_test_file_tree()?;
+        let root = temp_dir.path().join("root");
+        std::os::unix::fs::symlink(&root, root.join("data2/loop"))?;
+        let spec = OutputPathSpec::new().inputs(vec![root]).tree(true);
+        let (inputs, _outputs) = get_output_paths(spec)?;
+        assert_eq!(inputs.len(), 8);
+        Ok(())
+    }
 }
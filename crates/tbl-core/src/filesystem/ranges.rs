@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// inclusive range of values covered by a file, as in `name__START_to_END.parquet`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileRange {
+    /// path of file
+    pub path: PathBuf,
+    /// dataset name, the part of the file stem before the range
+    pub name: String,
+    /// first value covered by file
+    pub start: u64,
+    /// last value covered by file
+    pub end: u64,
+}
+
+/// range of values not covered by any file
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeGap {
+    /// dataset name
+    pub name: String,
+    /// first missing value
+    pub start: u64,
+    /// last missing value
+    pub end: u64,
+}
+
+/// range of values covered by more than one file
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeOverlap {
+    /// dataset name
+    pub name: String,
+    /// first value covered twice
+    pub start: u64,
+    /// last value covered twice
+    pub end: u64,
+    /// earlier file
+    pub first: PathBuf,
+    /// later file
+    pub second: PathBuf,
+}
+
+/// parse range from file name of form `name__START_to_END.ext`
+pub fn parse_file_range(path: &Path) -> Option<FileRange> {
+    let stem = path.file_stem()?.to_string_lossy().to_string();
+    let (name, range) = stem.rsplit_once("__")?;
+    let (start, end) = range.split_once("_to_")?;
+    let start = start.parse::<u64>().ok()?;
+    let end = end.parse::<u64>().ok()?;
+    if end < start {
+        return None;
+    }
+    Some(FileRange {
+        path: path.to_path_buf(),
+        name: name.to_string(),
+        start,
+        end,
+    })
+}
+
+/// find missing and overlapping ranges within each dataset name
+pub fn find_range_gaps_and_overlaps(ranges: &[FileRange]) -> (Vec<RangeGap>, Vec<RangeOverlap>) {
+    let mut by_name: BTreeMap<&str, Vec<&FileRange>> = BTreeMap::new();
+    for range in ranges.iter() {
+        by_name.entry(range.name.as_str()).or_default().push(range);
+    }
+
+    let mut gaps = Vec::new();
+    let mut overlaps = Vec::new();
+    for (name, mut ranges) in by_name.into_iter() {
+        ranges.sort_by_key(|range| (range.start, range.end));
+        let mut furthest: Option<&FileRange> = None;
+        for range in ranges.into_iter() {
+            if let Some(previous) = furthest {
+                if range.start > previous.end.saturating_add(1) {
+                    gaps.push(RangeGap {
+                        name: name.to_string(),
+                        start: previous.end + 1,
+                        end: range.start - 1,
+                    });
+                } else if range.start <= previous.end {
+                    overlaps.push(RangeOverlap {
+                        name: name.to_string(),
+                        start: range.start,
+                        end: std::cmp::min(range.end, previous.end),
+                        first: previous.path.clone(),
+                        second: range.path.clone(),
+                    });
+                }
+                if range.end > previous.end {
+                    furthest = Some(range);
+                }
+            } else {
+                furthest = Some(range);
+            }
+        }
+    }
+
+    (gaps, overlaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(path: &str) -> FileRange {
+        parse_file_range(Path::new(path)).expect("range should parse")
+    }
+
+    #[test]
+    fn test_parse_file_range() {
+        let parsed = range("/data/eth__blocks__00001000_to_00001999.parquet");
+        assert_eq!(parsed.name, "eth__blocks");
+        assert_eq!(parsed.start, 1000);
+        assert_eq!(parsed.end, 1999);
+        assert!(parse_file_range(Path::new("blocks.parquet")).is_none());
+        assert!(parse_file_range(Path::new("blocks__10_to_5.parquet")).is_none());
+    }
+
+    #[test]
+    fn test_gaps_and_overlaps() {
+        let ranges = vec![
+            range("blocks__00000000_to_00000999.parquet"),
+            range("blocks__00002000_to_00002999.parquet"),
+            range("blocks__00002500_to_00003499.parquet"),
+            range("txs__00000000_to_00000999.parquet"),
+            range("txs__00001000_to_00001999.parquet"),
+        ];
+        let (gaps, overlaps) = find_range_gaps_and_overlaps(&ranges);
+        assert_eq!(
+            gaps,
+            vec![RangeGap {
+                name: "blocks".to_string(),
+                start: 1000,
+                end: 1999
+            }]
+        );
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!((overlaps[0].start, overlaps[0].end), (2500, 2999));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::TblError;
+use futures::stream::TryStreamExt;
+use object_store::aws::AmazonS3Builder;
+use object_store::http::HttpBuilder;
+use object_store::path::Path as ObjectPath;
+use object_store::{ClientOptions, ObjectMeta, ObjectStore, WriteMultipart};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+
+const S3_PREFIX: &str = "s3://";
+const HTTP_PREFIXES: [&str; 2] = ["http://", "https://"];
+
+/// return true if path refers to a location in object storage or a web url
+///
+/// S3 credentials, region, and endpoint are read from the standard `AWS_*`
+/// environment variables, so S3-compatible stores such as MinIO can be used by
+/// setting `AWS_ENDPOINT_URL` and `AWS_ALLOW_HTTP=true`
+pub fn is_remote_path(path: &Path) -> bool {
+    path.to_str()
+        .map(|path| {
+            path.starts_with(S3_PREFIX) || HTTP_PREFIXES.iter().any(|p| path.starts_with(p))
+        })
+        .unwrap_or(false)
+}
+
+/// return true if path is an http or https url
+pub fn is_http_path(path: &Path) -> bool {
+    path.to_str()
+        .map(|path| HTTP_PREFIXES.iter().any(|p| path.starts_with(p)))
+        .unwrap_or(false)
+}
+
+/// split remote path into base url (bucket or host) and object key
+///
+/// the query string of an http url, such as a signature, is kept on the base
+/// url so that it is sent with every request for the object
+fn split_remote_path(path: &Path) -> Result<(String, String), TblError> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| TblError::InputError(format!("invalid remote path: {:?}", path)))?;
+    let scheme = std::iter::once(S3_PREFIX)
+        .chain(HTTP_PREFIXES)
+        .find(|scheme| path_str.starts_with(scheme))
+        .ok_or_else(|| TblError::InputError(format!("not a remote path: {}", path_str)))?;
+    let (path_str, query) = match path_str.split_once('?') {
+        Some((path_str, query)) if is_http_path(path) => (path_str, format!("?{}", query)),
+        _ => (path_str, String::new()),
+    };
+    let without_scheme = &path_str[scheme.len()..];
+    let (base, key) = match without_scheme.split_once('/') {
+        Some((base, key)) => (base, key.trim_matches('/')),
+        None => (without_scheme, ""),
+    };
+    if base.is_empty() {
+        return Err(TblError::InputError(format!(
+            "remote path has no bucket or host: {}",
+            path_str
+        )));
+    }
+    Ok((format!("{}{}{}", scheme, base, query), key.to_string()))
+}
+
+/// get object store and object location of remote path
+pub fn get_object_store(path: &Path) -> Result<(Arc<dyn ObjectStore>, ObjectPath), TblError> {
+    let (base_url, key) = split_remote_path(path)?;
+    let store: Arc<dyn ObjectStore> = if is_http_path(path) {
+        // plain http urls are rejected by the client unless allowed explicitly
+        let options = ClientOptions::new().with_allow_http(base_url.starts_with("http://"));
+        Arc::new(
+            HttpBuilder::new()
+                .with_url(base_url)
+                .with_client_options(options)
+                .build()?,
+        )
+    } else {
+        Arc::new(AmazonS3Builder::from_env().with_url(base_url).build()?)
+    };
+    Ok((store, ObjectPath::from(key)))
+}
+
+/// get object metadata of remote file
+pub async fn get_remote_object_meta(path: &Path) -> Result<ObjectMeta, TblError> {
+    let (store, location) = get_object_store(path)?;
+    Ok(store.head(&location).await?)
+}
+
+/// get size of remote file in bytes
+pub async fn get_remote_file_size(path: &Path) -> Result<u64, TblError> {
+    Ok(get_remote_object_meta(path).await?.size as u64)
+}
+
+/// return tabular files under remote prefix
+///
+/// if `tree` is false only files directly under the prefix are returned. http
+/// urls cannot be listed, so they always refer to a single file.
+pub async fn get_remote_tabular_files(path: &Path, tree: bool) -> Result<Vec<PathBuf>, TblError> {
+    if is_http_path(path) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let (bucket_url, key) = split_remote_path(path)?;
+    let (store, _) = get_object_store(path)?;
+    let prefix = if key.is_empty() {
+        None
+    } else {
+        Some(ObjectPath::from(key))
+    };
+
+    let locations: Vec<ObjectPath> = if tree {
+        store
+            .list(prefix.as_ref())
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await?
+    } else {
+        store
+            .list_with_delimiter(prefix.as_ref())
+            .await?
+            .objects
+            .into_iter()
+            .map(|meta| meta.location)
+            .collect()
+    };
+
+    let tabular_files = locations
+        .into_iter()
+        .map(|location| PathBuf::from(format!("{}/{}", bucket_url, location)))
+        .filter(|path| super::gather::is_tabular_file(path))
+        .collect();
+    Ok(tabular_files)
+}
+
+/// upload local file to remote path using a multipart upload
+pub async fn upload_file(local_path: &Path, remote_path: &Path) -> Result<(), TblError> {
+    const CHUNK_SIZE: usize = 8 * 1024 * 1024;
+    const MAX_CONCURRENCY: usize = 8;
+
+    let (store, location) = get_object_store(remote_path)?;
+    let upload = store.put_multipart(&location).await?;
+    let mut writer = WriteMultipart::new(upload);
+
+    let mut file = tokio::fs::File::open(local_path).await?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let n_read = file.read(&mut buffer).await?;
+        if n_read == 0 {
+            break;
+        }
+        writer.wait_for_capacity(MAX_CONCURRENCY).await?;
+        writer.write(&buffer[..n_read]);
+    }
+    writer.finish().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_remote_path() {
+        let path = Path::new("s3://bucket/some/prefix/data.parquet");
+        let (bucket, key) = split_remote_path(path).unwrap();
+        assert_eq!(bucket, "s3://bucket");
+        assert_eq!(key, "some/prefix/data.parquet");
+
+        let (bucket, key) = split_remote_path(Path::new("s3://bucket/")).unwrap();
+        assert_eq!(bucket, "s3://bucket");
+        assert_eq!(key, "");
+
+        let path = Path::new("https://example.com/files/data.parquet");
+        let (host, key) = split_remote_path(path).unwrap();
+        assert_eq!(host, "https://example.com");
+        assert_eq!(key, "files/data.parquet");
+        assert!(is_http_path(path));
+
+        let path = Path::new("https://example.com/files/data.parquet?token=a/b&x=1");
+        let (host, key) = split_remote_path(path).unwrap();
+        assert_eq!(host, "https://example.com?token=a/b&x=1");
+        assert_eq!(key, "files/data.parquet");
+
+        assert!(split_remote_path(Path::new("s3:///key")).is_err());
+        assert!(!is_remote_path(Path::new("/local/data.parquet")));
+    }
+
+    /// serve a file over http with range request support, requiring a query token
+    async fn serve_file(bytes: Vec<u8>, token: &'static str) -> String {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let bytes = Arc::new(bytes);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let bytes = bytes.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 4096];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let n_read = socket.read(&mut buffer).await.unwrap();
+                        if n_read == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buffer[..n_read]);
+                    }
+                    let request = String::from_utf8_lossy(&request).to_string();
+                    let mut lines = request.lines();
+                    let mut request_line = lines.next().unwrap_or_default().split(' ');
+                    let method = request_line.next().unwrap_or_default().to_string();
+                    let target = request_line.next().unwrap_or_default().to_string();
+                    let range = lines
+                        .filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.eq_ignore_ascii_case("range"))
+                        .and_then(|(_, value)| value.trim().strip_prefix("bytes="))
+                        .and_then(|range| range.split_once('-'))
+                        .map(|(start, end)| {
+                            let start: usize = start.parse().unwrap();
+                            let end: usize = end.parse().unwrap_or(bytes.len() - 1);
+                            (start, end.min(bytes.len() - 1))
+                        });
+
+                    let headers = "Connection: close\r\nLast-Modified: Mon, 01 Jan 2024 00:00:00 GMT\r\nETag: \"tbl\"\r\n";
+                    let response = if target != format!("/files/data.parquet?token={}", token) {
+                        format!(
+                            "HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n{}\r\n",
+                            headers
+                        )
+                        .into_bytes()
+                    } else if let Some((start, end)) = range {
+                        let mut response = format!(
+                            "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\n{}\r\n",
+                            end - start + 1,
+                            start,
+                            end,
+                            bytes.len(),
+                            headers
+                        )
+                        .into_bytes();
+                        response.extend_from_slice(&bytes[start..=end]);
+                        response
+                    } else {
+                        let mut response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n{}\r\n",
+                            bytes.len(),
+                            headers
+                        )
+                        .into_bytes();
+                        if method != "HEAD" {
+                            response.extend_from_slice(&bytes);
+                        }
+                        response
+                    };
+                    let _ = socket.write_all(&response).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+        format!("http://{}/files/data.parquet?token={}", address, token)
+    }
+
+    #[tokio::test]
+    async fn test_read_parquet_over_http() {
+        use polars::prelude::*;
+
+        let mut df = df!("block_number" => (0..1000u32).collect::<Vec<u32>>()).unwrap();
+        let mut bytes = Vec::new();
+        ParquetWriter::new(&mut bytes).finish(&mut df).unwrap();
+        let n_bytes = bytes.len() as u64;
+        let url = PathBuf::from(serve_file(bytes, "a%2Fb").await);
+
+        assert!(is_remote_path(&url));
+        assert_eq!(get_remote_file_size(&url).await.unwrap(), n_bytes);
+        assert_eq!(
+            get_remote_tabular_files(&url, true).await.unwrap(),
+            vec![url.clone()]
+        );
+        let n_rows = crate::parquet::get_parquet_row_count(&url).await.unwrap();
+        assert_eq!(n_rows, 1000);
+
+        // requests without the query string are rejected by the server
+        let without_query = PathBuf::from(url.to_str().unwrap().split('?').next().unwrap());
+        assert!(get_remote_file_size(&without_query).await.is_err());
+    }
+
+    /// round trip through an S3-compatible store such as MinIO
+    ///
+    /// run with `cargo test -- --ignored` after setting `TBL_TEST_S3_URL` to a
+    /// writable prefix such as `s3://tbl-test/ci`, along with the `AWS_*`
+    /// variables of the store
+    #[tokio::test]
+    #[ignore = "requires an S3-compatible store at TBL_TEST_S3_URL"]
+    async fn test_s3_round_trip() {
+        use polars::prelude::*;
+
+        let prefix = std::env::var("TBL_TEST_S3_URL").expect("TBL_TEST_S3_URL is not set");
+        let dir = tempfile::TempDir::new().unwrap();
+        let local_path = dir.path().join("data.parquet");
+        let mut df = df!("block_number" => (0..1000u32).collect::<Vec<u32>>()).unwrap();
+        let file = std::fs::File::create(&local_path).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+
+        let remote_prefix = PathBuf::from(format!(
+            "{}/tbl_test_{}",
+            prefix.trim_end_matches('/'),
+            std::process::id()
+        ));
+        let remote_path = remote_prefix.join("nested").join("data.parquet");
+        upload_file(&local_path, &remote_path).await.unwrap();
+
+        // listing
+        let tree = get_remote_tabular_files(&remote_prefix, true)
+            .await
+            .unwrap();
+        assert_eq!(tree, vec![remote_path.clone()]);
+        let flat = get_remote_tabular_files(&remote_prefix, false)
+            .await
+            .unwrap();
+        assert!(flat.is_empty());
+
+        // reading
+        let local_size = std::fs::metadata(&local_path).unwrap().len();
+        assert_eq!(
+            get_remote_file_size(&remote_path).await.unwrap(),
+            local_size
+        );
+        let n_rows = crate::parquet::get_parquet_row_count(&remote_path)
+            .await
+            .unwrap();
+        assert_eq!(n_rows, 1000);
+
+        let (store, location) = get_object_store(&remote_path).unwrap();
+        store.delete(&location).await.unwrap();
+    }
+}
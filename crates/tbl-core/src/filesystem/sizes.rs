@@ -1,17 +1,25 @@
+use crate::TblError;
 use futures::stream::{FuturesUnordered, StreamExt};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio::fs;
 
+/// get number of bytes of a local or remote file
+pub async fn get_file_size(path: &Path) -> Result<u64, TblError> {
+    if super::remote::is_remote_path(path) {
+        return super::remote::get_remote_file_size(path).await;
+    }
+    let metadata = fs::metadata(path).await?;
+    Ok(if metadata.is_file() {
+        metadata.len()
+    } else {
+        0
+    })
+}
+
 /// get total number of bytes across files
-pub async fn get_total_bytes_of_files(file_paths: &[&Path]) -> Result<u64, std::io::Error> {
-    let futures = file_paths.iter().map(|path| async move {
-        let metadata = fs::metadata(path).await?;
-        Ok::<u64, std::io::Error>(if metadata.is_file() {
-            metadata.len()
-        } else {
-            0
-        })
-    });
+pub async fn get_total_bytes_of_files(file_paths: &[&Path]) -> Result<u64, TblError> {
+    let futures = file_paths.iter().map(|path| get_file_size(path));
 
     let mut total: u64 = 0;
     let mut futures: FuturesUnordered<_> = futures.collect();
@@ -21,3 +29,47 @@ pub async fn get_total_bytes_of_files(file_paths: &[&Path]) -> Result<u64, std::
 
     Ok(total)
 }
+
+/// get last modification time of a local or remote file
+pub async fn get_file_modified(path: &Path) -> Result<SystemTime, TblError> {
+    if super::remote::is_remote_path(path) {
+        let meta = super::remote::get_remote_object_meta(path).await?;
+        return Ok(meta.last_modified.into());
+    }
+    Ok(fs::metadata(path).await?.modified()?)
+}
+
+/// keep only files with at least `min_bytes` bytes and modified since `modified_since`
+pub async fn filter_paths_by_file_stats(
+    paths: Vec<PathBuf>,
+    min_bytes: Option<u64>,
+    modified_since: Option<SystemTime>,
+) -> Result<Vec<PathBuf>, TblError> {
+    if min_bytes.is_none() && modified_since.is_none() {
+        return Ok(paths);
+    }
+    let keep = futures::stream::iter(paths.iter())
+        .map(|path| async move {
+            if let Some(min_bytes) = min_bytes {
+                if get_file_size(path).await? < min_bytes {
+                    return Ok(false);
+                }
+            }
+            if let Some(modified_since) = modified_since {
+                if get_file_modified(path).await? < modified_since {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        })
+        .buffered(10)
+        .collect::<Vec<Result<bool, TblError>>>()
+        .await;
+    let mut selected = Vec::new();
+    for (path, keep) in paths.into_iter().zip(keep) {
+        if keep? {
+            selected.push(path);
+        }
+    }
+    Ok(selected)
+}
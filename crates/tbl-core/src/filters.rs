@@ -0,0 +1,156 @@
+use crate::TblError;
+use std::cmp::Ordering;
+
+/// comparison operator of a column filter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterOperator {
+    /// equal to value
+    Eq,
+    /// not equal to value
+    NotEq,
+    /// greater than value
+    Gt,
+    /// less than value
+    Lt,
+    /// greater than or equal to value
+    GtEq,
+    /// less than or equal to value
+    LtEq,
+    /// value is null
+    IsNull,
+    /// value is not null
+    IsNotNull,
+}
+
+impl FilterOperator {
+    /// return true if an ordering of column value relative to filter value passes
+    pub fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            FilterOperator::Eq => ordering == Ordering::Equal,
+            FilterOperator::NotEq => ordering != Ordering::Equal,
+            FilterOperator::Gt => ordering == Ordering::Greater,
+            FilterOperator::Lt => ordering == Ordering::Less,
+            FilterOperator::GtEq => ordering != Ordering::Less,
+            FilterOperator::LtEq => ordering != Ordering::Greater,
+            FilterOperator::IsNull => false,
+            FilterOperator::IsNotNull => true,
+        }
+    }
+}
+
+/// filter on the values of a single column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnFilter {
+    /// column name
+    pub column: String,
+    /// comparison operator
+    pub operator: FilterOperator,
+    /// raw value to compare against, none for null checks
+    pub value: Option<String>,
+}
+
+/// parse filter of form `COLUMN=VALUE`, `COLUMN>VALUE`, or `COLUMN.is_null`
+///
+/// supported operators are `=`, `!=`, `>`, `<`, `>=`, `<=`, `.is_null`, and `.is_not_null`
+pub fn parse_column_filter(filter: &str) -> Result<ColumnFilter, TblError> {
+    let operators = [
+        ("!=", FilterOperator::NotEq),
+        (">=", FilterOperator::GtEq),
+        ("<=", FilterOperator::LtEq),
+        ("=", FilterOperator::Eq),
+        (">", FilterOperator::Gt),
+        ("<", FilterOperator::Lt),
+    ];
+    for (symbol, operator) in operators.into_iter() {
+        if filter.contains(symbol) {
+            let parts: Vec<&str> = filter.split(symbol).collect();
+            if parts.len() != 2 || parts[0].is_empty() {
+                return Err(TblError::InputError(format!(
+                    "invalid filter format: {}",
+                    filter
+                )));
+            }
+            return Ok(ColumnFilter {
+                column: parts[0].to_string(),
+                operator,
+                value: Some(parts[1].to_string()),
+            });
+        }
+    }
+
+    if let Some(column) = filter.strip_suffix(".is_null") {
+        Ok(ColumnFilter {
+            column: column.to_string(),
+            operator: FilterOperator::IsNull,
+            value: None,
+        })
+    } else if let Some(column) = filter.strip_suffix(".is_not_null") {
+        Ok(ColumnFilter {
+            column: column.to_string(),
+            operator: FilterOperator::IsNotNull,
+            value: None,
+        })
+    } else {
+        Err(TblError::InputError(format!(
+            "invalid filter format: {}",
+            filter
+        )))
+    }
+}
+
+/// parse integer written in decimal or as 0x-prefixed hex
+pub fn parse_filter_int(value: &str) -> Option<i128> {
+    match value.strip_prefix("0x") {
+        Some(hex_value) => i128::from_str_radix(hex_value, 16).ok(),
+        None => value.parse::<i128>().ok(),
+    }
+}
+
+impl ColumnFilter {
+    /// return true if a raw string value passes the filter
+    ///
+    /// values are compared as integers if both sides parse as integers, and as
+    /// strings otherwise
+    pub fn matches_str(&self, value: &str) -> bool {
+        let target = match &self.value {
+            Some(target) => target,
+            None => return self.operator.accepts(Ordering::Equal),
+        };
+        let ordering = match (parse_filter_int(value), parse_filter_int(target)) {
+            (Some(value), Some(target)) => value.cmp(&target),
+            _ => value.cmp(target.as_str()),
+        };
+        self.operator.accepts(ordering)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_column_filter() {
+        let filter = parse_column_filter("year>=2024").unwrap();
+        assert_eq!(filter.column, "year");
+        assert_eq!(filter.operator, FilterOperator::GtEq);
+        assert_eq!(filter.value.as_deref(), Some("2024"));
+
+        let filter = parse_column_filter("chain.is_not_null").unwrap();
+        assert_eq!(filter.operator, FilterOperator::IsNotNull);
+        assert_eq!(filter.value, None);
+
+        assert!(parse_column_filter("chain").is_err());
+        assert!(parse_column_filter("=1").is_err());
+    }
+
+    #[test]
+    fn test_matches_str() {
+        let filter = parse_column_filter("year>9").unwrap();
+        assert!(filter.matches_str("10"));
+        assert!(!filter.matches_str("9"));
+
+        let filter = parse_column_filter("chain!=eth").unwrap();
+        assert!(filter.matches_str("base"));
+        assert!(!filter.matches_str("eth"));
+    }
+}
@@ -20,4 +20,10 @@ pub mod types;
 /// formats
 pub mod formats;
 
+/// column filters
+pub mod filters;
+
+/// tabular file writers
+pub mod writers;
+
 pub use types::*;
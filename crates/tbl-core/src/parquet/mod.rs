@@ -1,3 +1,7 @@
+/// parquet reader functions
+pub mod parquet_reader;
+pub use parquet_reader::*;
+
 /// parquet summary functions
 pub mod parquet_summary;
 pub use parquet_summary::*;
@@ -21,3 +25,55 @@ pub use parquet_insert::*;
 /// parquet parquet_scan
 pub mod parquet_scan;
 pub use parquet_scan::*;
+
+/// parquet check functions
+pub mod parquet_check;
+pub use parquet_check::*;
+
+/// parquet statistics functions
+pub mod parquet_statistics;
+pub use parquet_statistics::*;
+
+/// parquet coverage functions
+pub mod parquet_coverage;
+pub use parquet_coverage::*;
+
+/// parquet key order functions
+pub mod parquet_order;
+pub use parquet_order::*;
+
+/// parquet sampling functions
+pub mod parquet_sample;
+pub use parquet_sample::*;
+
+/// parquet statistics pruning functions
+pub mod parquet_prune;
+pub use parquet_prune::*;
+
+/// parquet summary cache functions
+pub mod parquet_cache;
+pub use parquet_cache::*;
+
+/// parquet dataset manifest functions
+pub mod parquet_index;
+pub use parquet_index::*;
+
+/// parquet row group and page inspection functions
+pub mod parquet_inspect;
+pub use parquet_inspect::*;
+
+/// parquet key-value metadata functions
+pub mod parquet_metadata;
+pub use parquet_metadata::*;
+
+/// parquet layout preservation functions
+pub mod parquet_preserve;
+pub use parquet_preserve::*;
+
+/// parquet bloom filter and page index functions
+pub mod parquet_bloom;
+pub use parquet_bloom::*;
+
+/// parquet point lookup functions
+pub mod parquet_find;
+pub use parquet_find::*;
@@ -0,0 +1,247 @@
+use super::parquet_metadata::ARROW_SCHEMA_KEY;
+use super::parquet_preserve::get_parquet_compression;
+use crate::TblError;
+use arrow::datatypes::Schema;
+use arrow::record_batch::RecordBatch;
+use futures::stream::StreamExt;
+use parquet::arrow::arrow_to_parquet_schema;
+use parquet::arrow::arrow_writer::ArrowWriter;
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use parquet::file::properties::{EnabledStatistics, WriterProperties, WriterPropertiesBuilder};
+use parquet::schema::types::{ColumnPath, SchemaDescriptor};
+use polars::prelude::{DataFrame, IpcWriter, ParquetCompression, SerWriter};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::File;
+
+/// rows per row group when none is given, matching the polars parquet writer
+const DEFAULT_ROW_GROUP_SIZE: usize = 512 * 512;
+
+/// write dataframe to parquet with bloom filters for columns and page indexes for all columns
+///
+/// rows go straight through the arrow writer one row group at a time, so the
+/// file is written once instead of being written by polars and then rewritten
+pub fn write_parquet_with_bloom_filters(
+    df: &DataFrame,
+    path: &Path,
+    bloom_filter_columns: &[String],
+    compression: ParquetCompression,
+    row_group_size: Option<usize>,
+) -> Result<(), TblError> {
+    let row_group_size = row_group_size.unwrap_or(DEFAULT_ROW_GROUP_SIZE).max(1);
+    let (schema, _) = dataframe_to_record_batches(&df.slice(0, 0))?;
+    let schema_descr = arrow_to_parquet_schema(&schema)?;
+    validate_bloom_filter_columns(&schema_descr, bloom_filter_columns)?;
+
+    let props = WriterProperties::builder()
+        .set_statistics_enabled(EnabledStatistics::Page)
+        .set_max_row_group_size(row_group_size)
+        .set_compression(get_parquet_compression(compression).ok_or_else(|| {
+            TblError::Error(format!("cannot write {:?} compression", compression))
+        })?);
+    let props = set_bloom_filter_columns(props, bloom_filter_columns);
+    let file = std::fs::File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props.build()))?;
+    for offset in (0..df.height()).step_by(row_group_size) {
+        let (_, batches) = dataframe_to_record_batches(&df.slice(offset as i64, row_group_size))?;
+        for batch in batches.into_iter() {
+            writer.write(&RecordBatch::try_new(
+                schema.clone(),
+                batch.columns().to_vec(),
+            )?)?;
+        }
+    }
+    writer.close()?;
+    Ok(())
+}
+
+/// convert dataframe into arrow record batches through an in-memory ipc file
+fn dataframe_to_record_batches(
+    df: &DataFrame,
+) -> Result<(Arc<Schema>, Vec<RecordBatch>), TblError> {
+    let mut buffer = Vec::new();
+    IpcWriter::new(&mut buffer)
+        .with_pl_flavor(false)
+        .finish(&mut df.clone())?;
+    let reader = arrow::ipc::reader::FileReader::try_new(std::io::Cursor::new(buffer), None)?;
+    let schema = Arc::new(Schema::new(reader.schema().fields().clone()));
+    let batches = reader.collect::<Result<Vec<RecordBatch>, _>>()?;
+    Ok((schema, batches))
+}
+
+fn validate_bloom_filter_columns(
+    schema_descr: &SchemaDescriptor,
+    bloom_filter_columns: &[String],
+) -> Result<(), TblError> {
+    for column in bloom_filter_columns.iter() {
+        if !schema_descr
+            .columns()
+            .iter()
+            .any(|descr| &descr.path().string() == column)
+        {
+            return Err(TblError::SchemaError(format!(
+                "bloom filter column not found: {}",
+                column
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn set_bloom_filter_columns(
+    mut props: WriterPropertiesBuilder,
+    bloom_filter_columns: &[String],
+) -> WriterPropertiesBuilder {
+    for column in bloom_filter_columns.iter() {
+        let path = ColumnPath::new(column.split('.').map(|part| part.to_string()).collect());
+        props = props.set_column_bloom_filter_enabled(path, true);
+    }
+    props
+}
+
+/// rewrite parquet file with bloom filters for columns and page indexes for all columns
+///
+/// compression, row group size, sorting columns, and key-value metadata of the
+/// input are kept. output path may be the same as the input path
+pub async fn write_parquet_bloom_filters(
+    input_path: &Path,
+    output_path: &Path,
+    bloom_filter_columns: &[String],
+) -> Result<(), TblError> {
+    let input_file = File::open(input_path).await?;
+    let builder = ParquetRecordBatchStreamBuilder::new(input_file).await?;
+    let metadata = builder.metadata().clone();
+    validate_bloom_filter_columns(
+        metadata.file_metadata().schema_descr(),
+        bloom_filter_columns,
+    )?;
+
+    // carry over layout of input, with statistics for every page. row groups
+    // are written up to the size of the largest input row group, and each
+    // column keeps its own codec
+    let mut props = WriterProperties::builder().set_statistics_enabled(EnabledStatistics::Page);
+    let max_row_group_rows = metadata
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.num_rows() as usize)
+        .max();
+    if let Some(max_row_group_rows) = max_row_group_rows {
+        props = props.set_max_row_group_size(max_row_group_rows.max(1));
+    }
+    if let Some(row_group) = metadata.row_groups().first() {
+        props = props.set_sorting_columns(row_group.sorting_columns().cloned());
+        for column in row_group.columns().iter() {
+            props =
+                props.set_column_compression(column.column_path().clone(), column.compression());
+        }
+    }
+    let key_value_metadata: Vec<_> = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.key != ARROW_SCHEMA_KEY)
+        .cloned()
+        .collect();
+    if !key_value_metadata.is_empty() {
+        props = props.set_key_value_metadata(Some(key_value_metadata));
+    }
+    let props = set_bloom_filter_columns(props, bloom_filter_columns);
+
+    // key-value metadata is written separately, so it is left out of the arrow schema
+    let schema = Arc::new(Schema::new(builder.schema().fields().clone()));
+    let mut reader_stream = builder.build()?;
+    let tmp_output_path = super::parquet_drop::create_tmp_target(output_path);
+    let output_file = std::fs::File::create(&tmp_output_path)?;
+    let mut arrow_writer = ArrowWriter::try_new(output_file, schema.clone(), Some(props.build()))?;
+    while let Some(batch) = reader_stream.next().await {
+        let batch = RecordBatch::try_new(schema.clone(), batch?.columns().to_vec())?;
+        arrow_writer.write(&batch)?;
+    }
+    arrow_writer.close()?;
+    std::fs::rename(tmp_output_path, output_path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::parse_column_filter;
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_bloom_filters_and_page_indexes() {
+        let dir = TempDir::new().unwrap();
+        let n_rows = 10_000u32;
+        let mut df = df!(
+            "block_number" => (0..n_rows).collect::<Vec<u32>>(),
+            "tx_hash" => (0..n_rows).map(|i| format!("0x{:064x}", i * 7)).collect::<Vec<String>>()
+        )
+        .unwrap();
+        let rewritten_path = dir.path().join("rewritten.parquet");
+        let file = std::fs::File::create(&rewritten_path).unwrap();
+        ParquetWriter::new(file)
+            .with_compression(ParquetCompression::Snappy)
+            .with_row_group_size(Some(2_500))
+            .finish(&mut df)
+            .unwrap();
+        write_parquet_bloom_filters(&rewritten_path, &rewritten_path, &["tx_hash".to_string()])
+            .await
+            .unwrap();
+        let written_path = dir.path().join("written.parquet");
+        let options = crate::writers::FileWriteOptions {
+            compression: Some(ParquetCompression::Snappy),
+            row_group_size: Some(2_500),
+            bloom_filter_columns: vec!["tx_hash".to_string()],
+            ..Default::default()
+        };
+        crate::writers::write_lf_to_file(
+            df.lazy(),
+            &written_path,
+            crate::writers::OutputFileFormat::Parquet,
+            &options,
+        )
+        .unwrap();
+
+        for path in [rewritten_path, written_path] {
+            check_bloom_filters_and_page_indexes(&path, n_rows).await;
+        }
+    }
+
+    async fn check_bloom_filters_and_page_indexes(path: &Path, n_rows: u32) {
+        // row group size and codec of every column are kept
+        let metadata = crate::parquet::get_parquet_metadata(path).await.unwrap();
+        assert_eq!(metadata.num_row_groups(), 4);
+        for row_group in metadata.row_groups().iter() {
+            assert_eq!(row_group.num_rows(), 2_500);
+            for column in row_group.columns().iter() {
+                assert_eq!(column.compression(), parquet::basic::Compression::SNAPPY);
+            }
+        }
+
+        // bloom filters rule out row groups that statistics cannot
+        let present = format!("tx_hash=0x{:064x}", 7 * 7);
+        let absent = format!("tx_hash=0x{:064x}", 7 * 7 + 1);
+        for (filter, n_row_groups) in [(present, 1), (absent, 0)] {
+            let filters = vec![parse_column_filter(&filter).unwrap()];
+            let selection = crate::parquet::select_parquet_rows(path, &filters)
+                .await
+                .unwrap();
+            assert_eq!(selection.n_rows, n_rows as u64);
+            assert_eq!(selection.row_groups.len(), n_row_groups);
+        }
+
+        // statistics and page indexes select rows of one row group
+        let filters = vec![parse_column_filter("block_number=1234").unwrap()];
+        let selection = crate::parquet::select_parquet_rows(path, &filters)
+            .await
+            .unwrap();
+        assert_eq!(selection.row_groups, vec![0]);
+        let df = crate::parquet::read_selected_rows(&selection, None)
+            .await
+            .unwrap();
+        assert_eq!(df.height() as u64, selection.n_selected_rows);
+    }
+}
@@ -0,0 +1,368 @@
+use super::parquet_summary::{get_parquet_summary, TabularColumnSummary, TabularSummary};
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// name of environment variable that overrides the summary cache directory
+pub const CACHE_DIR_ENV_VAR: &str = "TBL_CACHE_DIR";
+
+/// get directory of summary cache
+///
+/// uses `$TBL_CACHE_DIR`, then `$XDG_CACHE_HOME/tbl`, then `~/.cache/tbl`
+pub fn get_default_cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(CACHE_DIR_ENV_VAR) {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+        return Some(PathBuf::from(dir).join("tbl"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache").join("tbl"))
+}
+
+/// cached summary of one file, valid while its size and mtime are unchanged
+#[derive(Clone)]
+struct CacheEntry {
+    n_bytes: u64,
+    modified_ns: i64,
+    summary: TabularSummary,
+}
+
+/// get summaries of parquet files, reusing summaries cached in `cache_dir`
+///
+/// one cache file is kept per input directory, entries are keyed by path, size
+/// and modification time so changed files are re-summarized automatically
+pub async fn get_parquet_summaries_cached(
+    paths: &[PathBuf],
+    cache_dir: &Path,
+) -> Result<Vec<TabularSummary>, TblError> {
+    // group local paths by directory
+    let mut keys: Vec<Option<(PathBuf, u64, i64)>> = Vec::with_capacity(paths.len());
+    let mut by_dir: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+    for (p, path) in paths.iter().enumerate() {
+        if crate::filesystem::is_remote_path(path) {
+            keys.push(None);
+            continue;
+        }
+        let path = tokio::fs::canonicalize(path).await?;
+        let metadata = tokio::fs::metadata(&path).await?;
+        let modified_ns = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as i64)
+            .unwrap_or(0);
+        let dir = path
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_default();
+        by_dir.entry(dir).or_default().push(p);
+        keys.push(Some((path, metadata.len(), modified_ns)));
+    }
+
+    let mut summaries: Vec<Option<TabularSummary>> = vec![None; paths.len()];
+    for (dir, indices) in by_dir.into_iter() {
+        let cache_path = get_cache_path(cache_dir, &dir);
+        let mut entries = read_cache_file(cache_path.clone()).await;
+        let mut dirty = false;
+        let mut missing = Vec::new();
+        for index in indices.into_iter() {
+            if let Some((path, n_bytes, modified_ns)) = &keys[index] {
+                match entries.get(path) {
+                    Some(entry)
+                        if entry.n_bytes == *n_bytes && entry.modified_ns == *modified_ns =>
+                    {
+                        summaries[index] = Some(entry.summary.clone())
+                    }
+                    _ => missing.push(index),
+                }
+            }
+        }
+        let computed = stream::iter(missing.iter())
+            .map(|index| get_parquet_summary(&paths[*index]))
+            .buffered(10)
+            .collect::<Vec<Result<TabularSummary, TblError>>>()
+            .await;
+        for (index, summary) in missing.into_iter().zip(computed) {
+            let summary = summary?;
+            if let Some((path, n_bytes, modified_ns)) = &keys[index] {
+                let entry = CacheEntry {
+                    n_bytes: *n_bytes,
+                    modified_ns: *modified_ns,
+                    summary: summary.clone(),
+                };
+                entries.insert(path.clone(), entry);
+                dirty = true;
+            }
+            summaries[index] = Some(summary);
+        }
+        if dirty {
+            write_cache_file(cache_path, entries).await?;
+        }
+    }
+
+    // summarize remote paths without cache
+    let mut results = Vec::with_capacity(paths.len());
+    for (path, summary) in paths.iter().zip(summaries) {
+        match summary {
+            Some(summary) => results.push(summary),
+            None => results.push(get_parquet_summary(path).await?),
+        }
+    }
+    Ok(results)
+}
+
+/// get number of rows of parquet files, reusing summaries cached in `cache_dir`
+pub async fn get_parquet_row_counts_cached(
+    paths: &[PathBuf],
+    cache_dir: &Path,
+) -> Result<Vec<u64>, TblError> {
+    Ok(get_parquet_summaries_cached(paths, cache_dir)
+        .await?
+        .iter()
+        .map(|summary| summary.n_rows)
+        .collect())
+}
+
+/// get path of cache file for a directory of inputs
+fn get_cache_path(cache_dir: &Path, dir: &Path) -> PathBuf {
+    let hash = fnv1a(dir.to_string_lossy().as_bytes());
+    cache_dir.join(format!("summaries_{:016x}.parquet", hash))
+}
+
+/// stable hash of bytes, used to name cache files
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes.iter() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// read cache file, treating a missing or unreadable cache as empty
+async fn read_cache_file(cache_path: PathBuf) -> HashMap<PathBuf, CacheEntry> {
+    tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&cache_path).ok()?;
+        let df = ParquetReader::new(file).finish().ok()?;
+        decode_cache_entries(&df).ok()
+    })
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+/// write cache file atomically so concurrent readers never see partial files
+async fn write_cache_file(
+    cache_path: PathBuf,
+    entries: HashMap<PathBuf, CacheEntry>,
+) -> Result<(), TblError> {
+    tokio::task::spawn_blocking(move || {
+        if let Some(parent) = cache_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut df = encode_cache_entries(&entries)?;
+        let tmp_path = cache_path.with_extension(format!("tmp{}", std::process::id()));
+        let file = std::fs::File::create(&tmp_path)?;
+        ParquetWriter::new(file).finish(&mut df)?;
+        std::fs::rename(&tmp_path, &cache_path)?;
+        Ok(())
+    })
+    .await?
+}
+
+fn encode_cache_entries(entries: &HashMap<PathBuf, CacheEntry>) -> Result<DataFrame, TblError> {
+    let mut paths = Vec::new();
+    let mut n_bytes = Vec::new();
+    let mut modified_ns = Vec::new();
+    let mut n_rows = Vec::new();
+    let mut n_bytes_uncompressed = Vec::new();
+    let mut schemas = Vec::new();
+    let mut columns_compressed = Vec::new();
+    let mut columns_uncompressed = Vec::new();
+    for (path, entry) in entries.iter() {
+        paths.push(path.to_string_lossy().to_string());
+        n_bytes.push(entry.n_bytes);
+        modified_ns.push(entry.modified_ns);
+        n_rows.push(entry.summary.n_rows);
+        n_bytes_uncompressed.push(entry.summary.n_bytes_uncompressed);
+        schemas.push(encode_schema(&entry.summary.schema)?);
+        let compressed: Vec<u64> = entry
+            .summary
+            .columns
+            .iter()
+            .map(|column| column.n_bytes_compressed)
+            .collect();
+        let uncompressed: Vec<u64> = entry
+            .summary
+            .columns
+            .iter()
+            .map(|column| column.n_bytes_uncompressed)
+            .collect();
+        columns_compressed.push(Series::new("", compressed));
+        columns_uncompressed.push(Series::new("", uncompressed));
+    }
+    Ok(DataFrame::new(vec![
+        Series::new("path", paths),
+        Series::new("n_bytes", n_bytes),
+        Series::new("modified_ns", modified_ns),
+        Series::new("n_rows", n_rows),
+        Series::new("n_bytes_uncompressed", n_bytes_uncompressed),
+        Series::new("schema", schemas),
+        Series::new("column_bytes_compressed", columns_compressed),
+        Series::new("column_bytes_uncompressed", columns_uncompressed),
+    ])?)
+}
+
+fn decode_cache_entries(df: &DataFrame) -> Result<HashMap<PathBuf, CacheEntry>, TblError> {
+    let paths = df.column("path")?.str()?;
+    let n_bytes = df.column("n_bytes")?.u64()?;
+    let modified_ns = df.column("modified_ns")?.i64()?;
+    let n_rows = df.column("n_rows")?.u64()?;
+    let n_bytes_uncompressed = df.column("n_bytes_uncompressed")?.u64()?;
+    let schemas = df.column("schema")?.binary()?;
+    let columns_compressed = df.column("column_bytes_compressed")?.list()?;
+    let columns_uncompressed = df.column("column_bytes_uncompressed")?.list()?;
+
+    let mut entries = HashMap::new();
+    for i in 0..df.height() {
+        let (path, schema) = match (paths.get(i), schemas.get(i)) {
+            (Some(path), Some(schema)) => (path, schema),
+            _ => continue,
+        };
+        let empty = || Series::new_empty("", &DataType::UInt64);
+        let compressed = columns_compressed.get_as_series(i).unwrap_or_else(empty);
+        let uncompressed = columns_uncompressed.get_as_series(i).unwrap_or_else(empty);
+        let columns = compressed
+            .u64()?
+            .into_no_null_iter()
+            .zip(uncompressed.u64()?.into_no_null_iter())
+            .map(
+                |(n_bytes_compressed, n_bytes_uncompressed)| TabularColumnSummary {
+                    n_bytes_compressed,
+                    n_bytes_uncompressed,
+                },
+            )
+            .collect();
+        let summary = TabularSummary {
+            n_files: 1,
+            n_bytes_compressed: n_bytes.get(i).unwrap_or(0),
+            n_bytes_uncompressed: n_bytes_uncompressed.get(i).unwrap_or(0),
+            n_rows: n_rows.get(i).unwrap_or(0),
+            schema: decode_schema(schema)?,
+            columns,
+        };
+        let entry = CacheEntry {
+            n_bytes: n_bytes.get(i).unwrap_or(0),
+            modified_ns: modified_ns.get(i).unwrap_or(0),
+            summary,
+        };
+        entries.insert(PathBuf::from(path), entry);
+    }
+    Ok(entries)
+}
+
+/// encode schema as an empty arrow ipc file so dtypes round trip exactly
+fn encode_schema(schema: &Schema) -> Result<Vec<u8>, TblError> {
+    let mut df = DataFrame::empty_with_schema(schema);
+    let mut bytes = Vec::new();
+    IpcWriter::new(&mut bytes).finish(&mut df)?;
+    Ok(bytes)
+}
+
+fn decode_schema(bytes: &[u8]) -> Result<Arc<Schema>, TblError> {
+    let df = IpcReader::new(std::io::Cursor::new(bytes)).finish()?;
+    Ok(Arc::new(df.schema()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_test_file(path: &Path, n_rows: u32) {
+        let mut df = df!("block_number" => (0..n_rows).collect::<Vec<u32>>()).unwrap();
+        let file = std::fs::File::create(path).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_parquet_summaries_cached_recomputes_changed_files() {
+        let data_dir = TempDir::new().unwrap();
+        let cache_dir = TempDir::new().unwrap();
+        let path = data_dir.path().join("data.parquet");
+        let paths = vec![path.clone()];
+
+        // first call computes and caches the summary
+        write_test_file(&path, 10);
+        let summaries = get_parquet_summaries_cached(&paths, cache_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(summaries[0].n_rows, 10);
+        assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 1);
+
+        // unchanged file is served from cache
+        let summaries = get_parquet_summaries_cached(&paths, cache_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(summaries[0].n_rows, 10);
+
+        // rewritten file changes size, so its entry is recomputed
+        write_test_file(&path, 1_000);
+        let summaries = get_parquet_summaries_cached(&paths, cache_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(summaries[0].n_rows, 1_000);
+        let cache_path = std::fs::read_dir(cache_dir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let entries = read_cache_file(cache_path).await;
+        let canonical = std::fs::canonicalize(&path).unwrap();
+        assert_eq!(entries[&canonical].summary.n_rows, 1_000);
+    }
+
+    #[test]
+    fn test_cache_entries_round_trip() {
+        let schema = Schema::from_iter(vec![
+            Field::new("block_number", DataType::UInt32),
+            Field::new("address", DataType::Binary),
+        ]);
+        let summary = TabularSummary {
+            n_files: 1,
+            n_bytes_compressed: 100,
+            n_bytes_uncompressed: 200,
+            n_rows: 10,
+            schema: Arc::new(schema),
+            columns: vec![
+                TabularColumnSummary {
+                    n_bytes_compressed: 40,
+                    n_bytes_uncompressed: 80,
+                },
+                TabularColumnSummary {
+                    n_bytes_compressed: 60,
+                    n_bytes_uncompressed: 120,
+                },
+            ],
+        };
+        let mut entries = HashMap::new();
+        let entry = CacheEntry {
+            n_bytes: 100,
+            modified_ns: 1_700_000_000_000_000_000,
+            summary: summary.clone(),
+        };
+        entries.insert(PathBuf::from("/data/a.parquet"), entry);
+
+        let df = encode_cache_entries(&entries).unwrap();
+        let decoded = decode_cache_entries(&df).unwrap();
+        let entry = &decoded[&PathBuf::from("/data/a.parquet")];
+        assert_eq!(entry.modified_ns, 1_700_000_000_000_000_000);
+        assert_eq!(entry.summary.n_rows, 10);
+        assert_eq!(entry.summary.schema, summary.schema);
+        assert_eq!(entry.summary.columns[1].n_bytes_uncompressed, 120);
+    }
+}
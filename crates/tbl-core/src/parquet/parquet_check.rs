@@ -0,0 +1,298 @@
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions};
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use parquet::file::metadata::ParquetMetaData;
+use std::path::{Path, PathBuf};
+
+/// problem detected while checking the integrity of a parquet file
+#[derive(Clone, Debug)]
+pub enum ParquetIssue {
+    /// footer or metadata could not be read, usually because file is truncated
+    UnreadableFooter(String),
+    /// file contains zero rows
+    ZeroRows,
+    /// file is a leftover temporary file of an interrupted write
+    TmpFile,
+    /// row count of file metadata does not match sum of row group row counts
+    FileRowCountMismatch {
+        /// rows according to file metadata
+        expected: u64,
+        /// rows according to row group metadata
+        actual: u64,
+    },
+    /// column chunk points past the end of the file
+    ColumnChunkOutOfBounds {
+        /// row group index
+        row_group: usize,
+        /// column path
+        column: String,
+    },
+    /// pages of row group could not be decoded
+    DecodeError {
+        /// row group index
+        row_group: usize,
+        /// decoding error
+        error: String,
+    },
+    /// decoded rows of row group do not match row group metadata
+    RowGroupRowCountMismatch {
+        /// row group index
+        row_group: usize,
+        /// rows according to row group metadata
+        expected: u64,
+        /// rows actually decoded
+        actual: u64,
+    },
+}
+
+impl std::fmt::Display for ParquetIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParquetIssue::UnreadableFooter(error) => write!(f, "unreadable footer: {}", error),
+            ParquetIssue::ZeroRows => write!(f, "file has zero rows"),
+            ParquetIssue::TmpFile => write!(f, "leftover temporary file"),
+            ParquetIssue::FileRowCountMismatch { expected, actual } => write!(
+                f,
+                "file metadata has {} rows but row groups have {} rows",
+                expected, actual
+            ),
+            ParquetIssue::ColumnChunkOutOfBounds { row_group, column } => write!(
+                f,
+                "row group {}: column chunk {} extends past end of file",
+                row_group, column
+            ),
+            ParquetIssue::DecodeError { row_group, error } => {
+                write!(f, "row group {}: decode error: {}", row_group, error)
+            }
+            ParquetIssue::RowGroupRowCountMismatch {
+                row_group,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "row group {}: metadata has {} rows but {} rows were decoded",
+                row_group, expected, actual
+            ),
+        }
+    }
+}
+
+/// result of checking the integrity of a parquet file
+#[derive(Clone, Debug)]
+pub struct ParquetCheck {
+    /// path of checked file
+    pub path: PathBuf,
+    /// number of bytes in file
+    pub n_bytes: u64,
+    /// number of rows according to metadata
+    pub n_rows: u64,
+    /// number of row groups according to metadata
+    pub n_row_groups: usize,
+    /// number of row groups that were decoded
+    pub n_row_groups_decoded: usize,
+    /// issues found in file
+    pub issues: Vec<ParquetIssue>,
+}
+
+impl ParquetCheck {
+    /// return true if no issues were found
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// check integrity of parquet file
+///
+/// validates the footer and metadata, then decodes every row group and compares
+/// decoded row counts against the metadata. if `quick` is set, only the first and
+/// last row groups are decoded.
+pub async fn check_parquet_file(path: &Path, quick: bool) -> Result<ParquetCheck, TblError> {
+    let n_bytes = crate::filesystem::get_file_size(path).await?;
+    let mut check = ParquetCheck {
+        path: path.to_path_buf(),
+        n_bytes,
+        n_rows: 0,
+        n_row_groups: 0,
+        n_row_groups_decoded: 0,
+        issues: Vec::new(),
+    };
+    if super::parquet_drop::is_tmp_target(path) {
+        check.issues.push(ParquetIssue::TmpFile);
+    }
+
+    // validate footer
+    let reader_metadata = match open_parquet_metadata(path).await {
+        Ok(reader_metadata) => reader_metadata,
+        Err(e) => {
+            check
+                .issues
+                .push(ParquetIssue::UnreadableFooter(e.to_string()));
+            return Ok(check);
+        }
+    };
+
+    // validate metadata
+    let metadata = reader_metadata.metadata().clone();
+    check.n_rows = metadata.file_metadata().num_rows() as u64;
+    check.n_row_groups = metadata.num_row_groups();
+    check
+        .issues
+        .extend(validate_parquet_metadata(&metadata, n_bytes));
+    if check.n_rows == 0 {
+        check.issues.push(ParquetIssue::ZeroRows);
+    }
+
+    // decode pages of each row group
+    let row_groups: Vec<usize> = if quick && check.n_row_groups > 2 {
+        vec![0, check.n_row_groups - 1]
+    } else {
+        (0..check.n_row_groups).collect()
+    };
+    for row_group in row_groups.into_iter() {
+        let expected = metadata.row_group(row_group).num_rows() as u64;
+        match count_decoded_rows(path, &reader_metadata, row_group).await {
+            Ok(actual) if actual == expected => {}
+            Ok(actual) => check.issues.push(ParquetIssue::RowGroupRowCountMismatch {
+                row_group,
+                expected,
+                actual,
+            }),
+            Err(e) => check.issues.push(ParquetIssue::DecodeError {
+                row_group,
+                error: e.to_string(),
+            }),
+        }
+        check.n_row_groups_decoded += 1;
+    }
+
+    Ok(check)
+}
+
+/// check integrity of multiple parquet files
+pub async fn check_parquet_files(
+    paths: &[PathBuf],
+    quick: bool,
+) -> Result<Vec<ParquetCheck>, TblError> {
+    let checks = stream::iter(paths)
+        .map(|path| check_parquet_file(path, quick))
+        .buffered(10)
+        .collect::<Vec<Result<ParquetCheck, TblError>>>()
+        .await;
+
+    checks
+        .into_iter()
+        .collect::<Result<Vec<ParquetCheck>, TblError>>()
+}
+
+async fn open_parquet_metadata(path: &Path) -> Result<ArrowReaderMetadata, TblError> {
+    let mut file = super::parquet_reader::open_parquet_file(path).await?;
+    Ok(ArrowReaderMetadata::load_async(&mut file, ArrowReaderOptions::new()).await?)
+}
+
+fn validate_parquet_metadata(metadata: &ParquetMetaData, n_bytes: u64) -> Vec<ParquetIssue> {
+    let mut issues = Vec::new();
+
+    let expected = metadata.file_metadata().num_rows() as u64;
+    let actual: u64 = metadata
+        .row_groups()
+        .iter()
+        .map(|rg| rg.num_rows() as u64)
+        .sum();
+    if expected != actual {
+        issues.push(ParquetIssue::FileRowCountMismatch { expected, actual });
+    }
+
+    for (row_group, rg) in metadata.row_groups().iter().enumerate() {
+        for column in rg.columns() {
+            let (start, length) = column.byte_range();
+            if start + length > n_bytes {
+                issues.push(ParquetIssue::ColumnChunkOutOfBounds {
+                    row_group,
+                    column: column.column_path().string(),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// decode a row group, reusing footer metadata that was parsed once per file
+async fn count_decoded_rows(
+    path: &Path,
+    reader_metadata: &ArrowReaderMetadata,
+    row_group: usize,
+) -> Result<u64, TblError> {
+    let file = super::parquet_reader::open_parquet_file(path).await?;
+    let mut reader_stream =
+        ParquetRecordBatchStreamBuilder::new_with_metadata(file, reader_metadata.clone())
+            .with_row_groups(vec![row_group])
+            .build()?;
+    let mut n_rows = 0;
+    while let Some(batch) = reader_stream.next().await {
+        n_rows += batch?.num_rows() as u64;
+    }
+    Ok(n_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    fn write_test_file(path: &Path, n_rows: u32, row_group_size: usize) {
+        let mut df = df!("block_number" => (0..n_rows).collect::<Vec<u32>>()).unwrap();
+        let file = std::fs::File::create(path).unwrap();
+        ParquetWriter::new(file)
+            .with_row_group_size(Some(row_group_size))
+            .finish(&mut df)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_parquet_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        write_test_file(&path, 1_000, 100);
+
+        let check = check_parquet_file(&path, false).await.unwrap();
+        assert!(check.is_ok(), "{:?}", check.issues);
+        assert_eq!(check.n_rows, 1_000);
+        assert_eq!(check.n_row_groups, 10);
+        assert_eq!(check.n_row_groups_decoded, 10);
+
+        let check = check_parquet_file(&path, true).await.unwrap();
+        assert!(check.is_ok());
+        assert_eq!(check.n_row_groups_decoded, 2);
+    }
+
+    #[tokio::test]
+    async fn test_check_truncated_parquet_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        write_test_file(&path, 1_000, 100);
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+
+        let check = check_parquet_file(&path, false).await.unwrap();
+        assert!(matches!(
+            check.issues.as_slice(),
+            [ParquetIssue::UnreadableFooter(_)]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_empty_parquet_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        write_test_file(&path, 0, 100);
+
+        let check = check_parquet_file(&path, false).await.unwrap();
+        assert!(check
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, ParquetIssue::ZeroRows)));
+    }
+}
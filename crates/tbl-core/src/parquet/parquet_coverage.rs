@@ -0,0 +1,212 @@
+use super::parquet_statistics::{get_parquet_column_statistics, StatValue};
+use crate::filesystem::{find_range_gaps_and_overlaps, parse_file_range, FileRange};
+use crate::filesystem::{RangeGap, RangeOverlap};
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use std::path::{Path, PathBuf};
+
+/// file whose contents fall outside of its declared range
+#[derive(Clone, Debug)]
+pub struct RangeViolation {
+    /// path of file
+    pub path: PathBuf,
+    /// declared first value
+    pub start: u64,
+    /// declared last value
+    pub end: u64,
+    /// minimum value of column
+    pub min: StatValue,
+    /// maximum value of column
+    pub max: StatValue,
+}
+
+/// report of missing, overlapping, and violated ranges across files
+#[derive(Clone, Debug, Default)]
+pub struct CoverageReport {
+    /// ranges of files
+    pub ranges: Vec<FileRange>,
+    /// files whose range could not be determined
+    pub unknown: Vec<PathBuf>,
+    /// ranges not covered by any file
+    pub gaps: Vec<RangeGap>,
+    /// ranges covered by multiple files
+    pub overlaps: Vec<RangeOverlap>,
+    /// files whose contents fall outside of declared range
+    pub violations: Vec<RangeViolation>,
+    /// files whose column statistics are missing or not integers, so their
+    /// contents could not be checked against their declared range
+    pub unchecked: Vec<PathBuf>,
+}
+
+/// get coverage report of range-named files
+///
+/// ranges are parsed from file names of form `name__START_to_END.parquet`. if
+/// `column` is given, the min and max statistics of that column are checked
+/// against each file's declared range. if `from_stats` is set, ranges are taken
+/// from the column statistics instead of the file names.
+pub async fn get_coverage_report(
+    paths: &[PathBuf],
+    column: Option<&str>,
+    from_stats: bool,
+) -> Result<CoverageReport, TblError> {
+    if from_stats && column.is_none() {
+        return Err(TblError::InputError(
+            "must specify column to use ranges from statistics".to_string(),
+        ));
+    }
+
+    let mut report = CoverageReport::default();
+    let column_ranges = match column {
+        Some(column) => get_column_ranges(paths, column).await?,
+        None => vec![None; paths.len()],
+    };
+    for (path, column_range) in paths.iter().zip(column_ranges) {
+        let declared = if from_stats {
+            column_range
+                .as_ref()
+                .and_then(|(min, max)| stats_to_file_range(path, column, min, max))
+        } else {
+            parse_file_range(path)
+        };
+        let declared = match declared {
+            Some(declared) => declared,
+            None => {
+                report.unknown.push(path.clone());
+                continue;
+            }
+        };
+
+        if column.is_some() {
+            match column_range {
+                Some((StatValue::Int(min), StatValue::Int(max))) => {
+                    if min < declared.start as i128 || max > declared.end as i128 {
+                        report.violations.push(RangeViolation {
+                            path: path.clone(),
+                            start: declared.start,
+                            end: declared.end,
+                            min: StatValue::Int(min),
+                            max: StatValue::Int(max),
+                        })
+                    }
+                }
+                _ => report.unchecked.push(path.clone()),
+            }
+        }
+
+        report.ranges.push(declared);
+    }
+
+    let (gaps, overlaps) = find_range_gaps_and_overlaps(&report.ranges);
+    report.gaps = gaps;
+    report.overlaps = overlaps;
+
+    Ok(report)
+}
+
+async fn get_column_ranges(
+    paths: &[PathBuf],
+    column: &str,
+) -> Result<Vec<Option<(StatValue, StatValue)>>, TblError> {
+    let ranges = stream::iter(paths)
+        .map(|path| get_column_range(path, column))
+        .buffered(10)
+        .collect::<Vec<Result<Option<(StatValue, StatValue)>, TblError>>>()
+        .await;
+
+    ranges
+        .into_iter()
+        .collect::<Result<Vec<Option<(StatValue, StatValue)>>, TblError>>()
+}
+
+async fn get_column_range(
+    path: &Path,
+    column: &str,
+) -> Result<Option<(StatValue, StatValue)>, TblError> {
+    let metadata = super::parquet_summary::get_parquet_metadata(path).await?;
+    match get_parquet_column_statistics(&metadata, column) {
+        Some(stats) => match (stats.min, stats.max) {
+            (Some(min), Some(max)) => Ok(Some((min, max))),
+            _ => Ok(None),
+        },
+        None => Ok(None),
+    }
+}
+
+fn stats_to_file_range(
+    path: &Path,
+    column: Option<&str>,
+    min: &StatValue,
+    max: &StatValue,
+) -> Option<FileRange> {
+    match (min, max) {
+        (StatValue::Int(min), StatValue::Int(max)) => Some(FileRange {
+            path: path.to_path_buf(),
+            name: column.unwrap_or_default().to_string(),
+            start: u64::try_from(*min).ok()?,
+            end: u64::try_from(*max).ok()?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    fn write_test_file(dir: &Path, name: &str, mut df: DataFrame) -> PathBuf {
+        let path = dir.join(name);
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_get_coverage_report() {
+        let dir = TempDir::new().unwrap();
+        let blocks = |start: u32, end: u32| {
+            df!("block_number" => (start..=end).collect::<Vec<u32>>()).unwrap()
+        };
+        let paths = vec![
+            write_test_file(dir.path(), "blocks__0_to_99.parquet", blocks(0, 99)),
+            write_test_file(dir.path(), "blocks__100_to_199.parquet", blocks(100, 250)),
+            write_test_file(dir.path(), "blocks__300_to_399.parquet", blocks(300, 399)),
+        ];
+
+        let report = get_coverage_report(&paths, Some("block_number"), false)
+            .await
+            .unwrap();
+        assert_eq!(report.ranges.len(), 3);
+        assert_eq!(report.gaps.len(), 1);
+        assert_eq!((report.gaps[0].start, report.gaps[0].end), (200, 299));
+        assert!(report.overlaps.is_empty());
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].path, paths[1]);
+        assert!(report.unchecked.is_empty());
+
+        // ranges taken from statistics extend past declared ranges, shrinking the gap
+        let report = get_coverage_report(&paths, Some("block_number"), true)
+            .await
+            .unwrap();
+        assert_eq!(report.overlaps.len(), 0);
+        assert_eq!(report.gaps.len(), 1);
+        assert_eq!((report.gaps[0].start, report.gaps[0].end), (251, 299));
+    }
+
+    #[tokio::test]
+    async fn test_coverage_of_non_integer_column() {
+        let dir = TempDir::new().unwrap();
+        let df = df!("price" => [0.5f64, 1e9], "name" => ["a", "zzz"]).unwrap();
+        let paths = vec![write_test_file(dir.path(), "prices__0_to_1.parquet", df)];
+
+        // float and string statistics are not compared against integer ranges
+        for column in ["price", "name"] {
+            let report = get_coverage_report(&paths, Some(column), false)
+                .await
+                .unwrap();
+            assert!(report.violations.is_empty());
+            assert_eq!(report.unchecked, paths);
+        }
+    }
+}
@@ -27,6 +27,13 @@ pub fn create_tmp_target(path: &std::path::Path) -> PathBuf {
     new_path
 }
 
+/// return true if path looks like a temporary target created by `create_tmp_target`
+pub fn is_tmp_target(path: &std::path::Path) -> bool {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().ends_with("_tmp"))
+        .unwrap_or(false)
+}
+
 /// drop columns from parquet column
 pub async fn drop_parquet_columns(
     input_path: PathBuf,
@@ -0,0 +1,151 @@
+use super::parquet_prune::{
+    read_selected_rows, select_parquets_rows, ParquetRowSelection, PruneSummary,
+};
+use crate::filters::ColumnFilter;
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use parquet::arrow::arrow_reader::RowSelector;
+use polars::prelude::*;
+use std::path::PathBuf;
+
+/// name of column holding the path of the file that each found row comes from
+pub const FIND_PATH_COLUMN: &str = "path";
+
+/// name of column holding the offset of each found row within its file
+pub const FIND_ROW_OFFSET_COLUMN: &str = "row_offset";
+
+/// get offset within file of each row that a selection reads, in read order
+pub fn get_selected_row_offsets(selection: &ParquetRowSelection) -> Vec<u64> {
+    let mut rows = selection
+        .row_group_rows
+        .iter()
+        .flat_map(|range| range.clone());
+    match &selection.selection {
+        None => rows.collect(),
+        Some(row_selection) => {
+            let selectors: Vec<RowSelector> = row_selection.clone().into();
+            let mut offsets = Vec::with_capacity(selection.n_selected_rows as usize);
+            for selector in selectors.into_iter() {
+                if selector.skip {
+                    if selector.row_count > 0 {
+                        rows.nth(selector.row_count - 1);
+                    }
+                } else {
+                    offsets.extend(rows.by_ref().take(selector.row_count));
+                }
+            }
+            offsets
+        }
+    }
+}
+
+/// find rows of parquet files that pass every filter
+///
+/// files, row groups, and pages are ruled out using statistics, bloom filters,
+/// and page indexes, then the remaining rows are read with up to
+/// `max_concurrent` files in flight. each file is filtered by `predicate` as
+/// soon as it is read, so only found rows are held in memory. each found row
+/// is prefixed with its source path and its row offset within that file
+pub async fn find_parquet_rows(
+    paths: &[PathBuf],
+    filters: &[ColumnFilter],
+    predicate: Expr,
+    max_concurrent: usize,
+) -> Result<(DataFrame, PruneSummary), TblError> {
+    let (selections, summary) = select_parquets_rows(paths, filters, max_concurrent).await?;
+    let dfs = stream::iter(selections.iter())
+        .map(|selection| find_selected_rows(selection, predicate.clone()))
+        .buffered(max_concurrent.max(1))
+        .collect::<Vec<Result<DataFrame, TblError>>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<DataFrame>, TblError>>()?;
+    if dfs.is_empty() {
+        return Ok((DataFrame::empty(), summary));
+    }
+    let lfs = dfs.into_iter().map(|df| df.lazy()).collect::<Vec<_>>();
+    let df = concat(lfs, UnionArgs::default())?.collect()?;
+    Ok((df, summary))
+}
+
+async fn find_selected_rows(
+    selection: &ParquetRowSelection,
+    predicate: Expr,
+) -> Result<DataFrame, TblError> {
+    let mut df = read_selected_rows(selection, None).await?;
+    let offsets = get_selected_row_offsets(selection);
+    if offsets.len() != df.height() {
+        return Err(TblError::Error(format!(
+            "read {} rows but selected {} rows of {}",
+            df.height(),
+            offsets.len(),
+            selection.path.display()
+        )));
+    }
+    let path = selection.path.to_string_lossy().to_string();
+    df.insert_column(0, Series::new(FIND_ROW_OFFSET_COLUMN, offsets))?;
+    df.insert_column(0, Series::new(FIND_PATH_COLUMN, vec![path; df.height()]))?;
+    Ok(df.lazy().filter(predicate).collect()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::parse_column_filter;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_find_parquet_rows() {
+        let dir = TempDir::new().unwrap();
+        let mut paths = Vec::new();
+        for (f, start) in [0u32, 1_000, 2_000].into_iter().enumerate() {
+            let path = dir.path().join(format!("data_{}.parquet", f));
+            let mut df = df!(
+                "block_number" => (start..start + 1_000).collect::<Vec<u32>>(),
+                "value" => (start..start + 1_000).map(|i| i % 7).collect::<Vec<u32>>()
+            )
+            .unwrap();
+            let file = std::fs::File::create(&path).unwrap();
+            ParquetWriter::new(file)
+                .with_row_group_size(Some(250))
+                .finish(&mut df)
+                .unwrap();
+            paths.push(path);
+        }
+
+        let filters = vec![parse_column_filter("block_number=1600").unwrap()];
+        let predicate = col("block_number").eq(lit(1600u32));
+        let (df, summary) = find_parquet_rows(&paths, &filters, predicate, 4)
+            .await
+            .unwrap();
+        assert_eq!(summary.n_skipped_files, 2);
+        assert_eq!(summary.n_skipped_row_groups, 11);
+        assert_eq!(df.height(), 1);
+        let row_offset = df.column(FIND_ROW_OFFSET_COLUMN).unwrap().u64().unwrap();
+        assert_eq!(row_offset.get(0), Some(600));
+        let path = df.column(FIND_PATH_COLUMN).unwrap().str().unwrap();
+        assert_eq!(path.get(0), Some(paths[1].to_string_lossy().as_ref()));
+    }
+
+    #[test]
+    fn test_get_selected_row_offsets() {
+        let selection = ParquetRowSelection {
+            path: PathBuf::from("data.parquet"),
+            n_row_groups: 3,
+            n_rows: 30,
+            row_groups: vec![0, 2],
+            row_group_rows: vec![0..10, 20..30],
+            selection: Some(
+                vec![
+                    RowSelector::skip(8),
+                    RowSelector::select(4),
+                    RowSelector::skip(7),
+                    RowSelector::select(1),
+                ]
+                .into(),
+            ),
+            n_selected_rows: 5,
+        };
+        assert_eq!(get_selected_row_offsets(&selection), vec![8, 9, 20, 21, 29]);
+    }
+}
@@ -0,0 +1,580 @@
+use super::parquet_prune::{statistics_may_match, PruneSummary};
+use super::parquet_scan::record_batches_to_dataframe;
+use super::parquet_statistics::{
+    get_parquet_column_statistics, get_stat_type, ColumnStatistics, StatType, StatValue,
+};
+use super::parquet_summary::{get_parquet_metadata, FileSelection};
+use crate::filesystem::{get_file_size, is_remote_path, parse_file_range, FileRange};
+use crate::filters::ColumnFilter;
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use parquet::arrow::parquet_to_arrow_schema;
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// statistics of one leaf column in a dataset manifest
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexColumn {
+    /// dotted path of column
+    pub name: String,
+    /// kind of statistic values
+    pub stat_type: StatType,
+    /// min, max, and null count across row groups
+    pub stats: ColumnStatistics,
+}
+
+/// description of one file in a dataset manifest
+#[derive(Clone, Debug)]
+pub struct FileIndexEntry {
+    /// path of file
+    pub path: PathBuf,
+    /// number of bytes on disk
+    pub n_bytes: u64,
+    /// number of rows
+    pub n_rows: u64,
+    /// number of row groups
+    pub n_row_groups: u64,
+    /// hash of file schema, equal for files with identical schemas
+    pub schema_hash: String,
+    /// statistics of each leaf column, in schema order
+    pub columns: Vec<IndexColumn>,
+    /// range parsed from file name
+    pub range: Option<FileRange>,
+}
+
+/// get stable hash of schema column names and types
+pub fn get_schema_hash(schema: &Schema) -> String {
+    let description: String = schema
+        .iter()
+        .map(|(name, dtype)| format!("{}:{};", name, dtype))
+        .collect();
+    format!(
+        "{:016x}",
+        super::parquet_cache::fnv1a(description.as_bytes())
+    )
+}
+
+/// describe one parquet file using only its footer, which is read once
+pub async fn get_file_index_entry(path: &Path) -> Result<FileIndexEntry, TblError> {
+    let n_bytes = get_file_size(path).await?;
+    let metadata = get_parquet_metadata(path).await?;
+    let file_metadata = metadata.file_metadata();
+    let arrow_schema = parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )?;
+    let schema = record_batches_to_dataframe(std::sync::Arc::new(arrow_schema), &[])?.schema();
+    let columns = file_metadata
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| {
+            let name = column.path().string();
+            let stats = get_parquet_column_statistics(&metadata, &name).unwrap_or_default();
+            IndexColumn {
+                name,
+                stat_type: get_stat_type(column),
+                stats,
+            }
+        })
+        .collect();
+    Ok(FileIndexEntry {
+        path: path.to_path_buf(),
+        n_bytes,
+        n_rows: file_metadata.num_rows() as u64,
+        n_row_groups: metadata.num_row_groups() as u64,
+        schema_hash: get_schema_hash(&schema),
+        columns,
+        range: parse_file_range(path),
+    })
+}
+
+/// describe parquet files using only their footers
+pub async fn get_file_index_entries(paths: &[PathBuf]) -> Result<Vec<FileIndexEntry>, TblError> {
+    stream::iter(paths)
+        .map(|path| get_file_index_entry(path))
+        .buffered(10)
+        .collect::<Vec<Result<FileIndexEntry, TblError>>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// build manifest dataframe with one row per file
+///
+/// column statistics are stored as parallel lists of column names, stat types,
+/// min and max values rendered as strings, null counts, and min and max values
+/// of integer and float columns as numbers. integers outside the i64 range only
+/// have string values
+pub fn build_index_dataframe(entries: &[FileIndexEntry]) -> Result<DataFrame, TblError> {
+    let mut paths = Vec::new();
+    let mut n_bytes = Vec::new();
+    let mut n_rows = Vec::new();
+    let mut n_row_groups = Vec::new();
+    let mut schema_hashes = Vec::new();
+    let mut range_names = Vec::new();
+    let mut range_starts = Vec::new();
+    let mut range_ends = Vec::new();
+    let mut column_names = Vec::new();
+    let mut column_types = Vec::new();
+    let mut column_mins = Vec::new();
+    let mut column_maxs = Vec::new();
+    let mut column_int_mins = Vec::new();
+    let mut column_int_maxs = Vec::new();
+    let mut column_float_mins = Vec::new();
+    let mut column_float_maxs = Vec::new();
+    let mut column_null_counts = Vec::new();
+    for entry in entries.iter() {
+        paths.push(entry.path.to_string_lossy().to_string());
+        n_bytes.push(entry.n_bytes);
+        n_rows.push(entry.n_rows);
+        n_row_groups.push(entry.n_row_groups);
+        schema_hashes.push(entry.schema_hash.clone());
+        range_names.push(entry.range.as_ref().map(|range| range.name.clone()));
+        range_starts.push(entry.range.as_ref().map(|range| range.start));
+        range_ends.push(entry.range.as_ref().map(|range| range.end));
+
+        let columns = &entry.columns;
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        let types: Vec<&str> = columns.iter().map(|c| c.stat_type.name()).collect();
+        let render = |c: &IndexColumn, value: &Option<StatValue>| {
+            value
+                .as_ref()
+                .map(|value| render_stat_value(value, c.stat_type))
+        };
+        let int = |value: &Option<StatValue>| match value {
+            Some(StatValue::Int(value)) => i64::try_from(*value).ok(),
+            _ => None,
+        };
+        let float = |value: &Option<StatValue>| match value {
+            Some(StatValue::Float(value)) => Some(*value),
+            _ => None,
+        };
+        let mins: Vec<Option<String>> = columns.iter().map(|c| render(c, &c.stats.min)).collect();
+        let maxs: Vec<Option<String>> = columns.iter().map(|c| render(c, &c.stats.max)).collect();
+        let int_mins: Vec<Option<i64>> = columns.iter().map(|c| int(&c.stats.min)).collect();
+        let int_maxs: Vec<Option<i64>> = columns.iter().map(|c| int(&c.stats.max)).collect();
+        let float_mins: Vec<Option<f64>> = columns.iter().map(|c| float(&c.stats.min)).collect();
+        let float_maxs: Vec<Option<f64>> = columns.iter().map(|c| float(&c.stats.max)).collect();
+        let null_counts: Vec<Option<u64>> = columns.iter().map(|c| c.stats.null_count).collect();
+        column_names.push(Series::new("", names));
+        column_types.push(Series::new("", types));
+        column_mins.push(Series::new("", mins));
+        column_maxs.push(Series::new("", maxs));
+        column_int_mins.push(Series::new("", int_mins));
+        column_int_maxs.push(Series::new("", int_maxs));
+        column_float_mins.push(Series::new("", float_mins));
+        column_float_maxs.push(Series::new("", float_maxs));
+        column_null_counts.push(Series::new("", null_counts));
+    }
+
+    let list_column = |name: &str, values: Vec<Series>, dtype: DataType| {
+        if values.is_empty() {
+            Series::new_empty(name, &DataType::List(Box::new(dtype)))
+        } else {
+            Series::new(name, values)
+        }
+    };
+    Ok(DataFrame::new(vec![
+        Series::new("path", paths),
+        Series::new("n_bytes", n_bytes),
+        Series::new("n_rows", n_rows),
+        Series::new("n_row_groups", n_row_groups),
+        Series::new("schema_hash", schema_hashes),
+        Series::new("range_name", range_names),
+        Series::new("range_start", range_starts),
+        Series::new("range_end", range_ends),
+        list_column("column_names", column_names, DataType::String),
+        list_column("column_types", column_types, DataType::String),
+        list_column("column_mins", column_mins, DataType::String),
+        list_column("column_maxs", column_maxs, DataType::String),
+        list_column("column_int_mins", column_int_mins, DataType::Int64),
+        list_column("column_int_maxs", column_int_maxs, DataType::Int64),
+        list_column("column_float_mins", column_float_mins, DataType::Float64),
+        list_column("column_float_maxs", column_float_maxs, DataType::Float64),
+        list_column("column_null_counts", column_null_counts, DataType::UInt64),
+    ])?)
+}
+
+/// render statistic value as a string that `parse_stat_value` reads back exactly
+///
+/// binary values are always written as hex so that they are never mistaken for strings
+fn render_stat_value(value: &StatValue, stat_type: StatType) -> String {
+    match value {
+        StatValue::Bytes(bytes) if stat_type == StatType::Binary => {
+            format!("0x{}", hex::encode(bytes))
+        }
+        value => value.to_string(),
+    }
+}
+
+/// parse statistic value written by `render_stat_value`
+fn parse_stat_value(value: &str, stat_type: StatType) -> Option<StatValue> {
+    match stat_type {
+        StatType::Boolean => value.parse::<bool>().ok().map(StatValue::Boolean),
+        StatType::Int => value.parse::<i128>().ok().map(StatValue::Int),
+        StatType::Float => value.parse::<f64>().ok().map(StatValue::Float),
+        StatType::String => Some(StatValue::Bytes(value.as_bytes().to_vec())),
+        StatType::Binary => value
+            .strip_prefix("0x")
+            .and_then(|hex_value| hex::decode(hex_value).ok())
+            .map(StatValue::Bytes),
+        StatType::Other => None,
+    }
+}
+
+/// write manifest of parquet files to `output_path`
+///
+/// paths below the directory of the manifest are stored relative to it, so
+/// that a dataset and its manifest can be moved together
+pub async fn write_parquet_index(
+    paths: &[PathBuf],
+    output_path: &Path,
+) -> Result<Vec<FileIndexEntry>, TblError> {
+    let mut entries = get_file_index_entries(paths).await?;
+    let root = get_index_root(output_path)?;
+    for entry in entries
+        .iter_mut()
+        .filter(|entry| !is_remote_path(&entry.path))
+    {
+        let absolute = std::path::absolute(&entry.path)?;
+        if let Ok(relative) = absolute.strip_prefix(&root) {
+            entry.path = relative.to_path_buf();
+        }
+    }
+    let mut df = build_index_dataframe(&entries)?;
+    let output_path = output_path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<(), TblError> {
+        let file = std::fs::File::create(&output_path)?;
+        ParquetWriter::new(file).finish(&mut df)?;
+        Ok(())
+    })
+    .await??;
+    Ok(entries)
+}
+
+/// read file paths listed in a manifest written by `write_parquet_index`
+///
+/// relative paths are resolved against the directory of the manifest
+pub fn read_index_paths(index_path: &Path) -> Result<Vec<PathBuf>, TblError> {
+    let file = std::fs::File::open(index_path)?;
+    let df = ParquetReader::new(file)
+        .with_columns(Some(vec!["path".to_string()]))
+        .finish()?;
+    let root = get_index_root(index_path)?;
+    Ok(df
+        .column("path")?
+        .str()?
+        .into_iter()
+        .flatten()
+        .map(|path| resolve_index_path(&root, path))
+        .collect())
+}
+
+/// read entries of a manifest written by `write_parquet_index`
+///
+/// paths are resolved like those of `read_index_paths`, so entries can be
+/// matched with the input paths that the manifest lists
+pub fn read_parquet_index(index_path: &Path) -> Result<Vec<FileIndexEntry>, TblError> {
+    let file = std::fs::File::open(index_path)?;
+    let df = ParquetReader::new(file).finish()?;
+    let root = get_index_root(index_path)?;
+    let list = |name: &str| -> Result<Vec<Option<Series>>, TblError> {
+        Ok(df.column(name)?.list()?.into_iter().collect())
+    };
+    let paths = df.column("path")?.str()?;
+    let n_bytes = df.column("n_bytes")?.u64()?;
+    let n_rows = df.column("n_rows")?.u64()?;
+    let n_row_groups = df.column("n_row_groups")?.u64()?;
+    let schema_hashes = df.column("schema_hash")?.str()?;
+    let column_names = list("column_names")?;
+    let column_types = list("column_types")?;
+    let column_mins = list("column_mins")?;
+    let column_maxs = list("column_maxs")?;
+    let column_float_mins = list("column_float_mins")?;
+    let column_float_maxs = list("column_float_maxs")?;
+    let column_null_counts = list("column_null_counts")?;
+
+    let mut entries = Vec::new();
+    for i in 0..df.height() {
+        let path = match paths.get(i) {
+            Some(path) => resolve_index_path(&root, path),
+            None => continue,
+        };
+        let strings = |lists: &[Option<Series>]| -> Result<Vec<Option<String>>, TblError> {
+            match &lists[i] {
+                Some(values) => Ok(values
+                    .str()?
+                    .into_iter()
+                    .map(|value| value.map(|value| value.to_string()))
+                    .collect()),
+                None => Ok(Vec::new()),
+            }
+        };
+        let floats = |lists: &[Option<Series>]| -> Result<Vec<Option<f64>>, TblError> {
+            match &lists[i] {
+                Some(values) => Ok(values.f64()?.into_iter().collect()),
+                None => Ok(Vec::new()),
+            }
+        };
+        let names = strings(&column_names)?;
+        let types = strings(&column_types)?;
+        let mins = strings(&column_mins)?;
+        let maxs = strings(&column_maxs)?;
+        let float_mins = floats(&column_float_mins)?;
+        let float_maxs = floats(&column_float_maxs)?;
+        let null_counts: Vec<Option<u64>> = match &column_null_counts[i] {
+            Some(values) => values.u64()?.into_iter().collect(),
+            None => Vec::new(),
+        };
+        let columns = names
+            .into_iter()
+            .enumerate()
+            .map(|(c, name)| {
+                let stat_type = types.get(c).cloned().flatten().unwrap_or_default();
+                let stat_type = StatType::from_name(&stat_type);
+                // floats are read from their numeric values, which round trip exactly
+                let parse = |strings: &[Option<String>], floats: &[Option<f64>]| match stat_type {
+                    StatType::Float => floats.get(c).copied().flatten().map(StatValue::Float),
+                    _ => strings
+                        .get(c)
+                        .cloned()
+                        .flatten()
+                        .and_then(|value| parse_stat_value(&value, stat_type)),
+                };
+                IndexColumn {
+                    name: name.unwrap_or_default(),
+                    stat_type,
+                    stats: ColumnStatistics {
+                        min: parse(&mins, &float_mins),
+                        max: parse(&maxs, &float_maxs),
+                        null_count: null_counts.get(c).copied().flatten(),
+                    },
+                }
+            })
+            .collect();
+        entries.push(FileIndexEntry {
+            range: parse_file_range(&path),
+            path,
+            n_bytes: n_bytes.get(i).unwrap_or_default(),
+            n_rows: n_rows.get(i).unwrap_or_default(),
+            n_row_groups: n_row_groups.get(i).unwrap_or_default(),
+            schema_hash: schema_hashes.get(i).unwrap_or_default().to_string(),
+            columns,
+        });
+    }
+    Ok(entries)
+}
+
+/// return true if manifest entry satisfies the row, byte, and schema predicates
+/// of selection, without reading the file
+///
+/// modification times are not stored in manifests, so `modified_since` is ignored
+pub fn index_entry_matches_selection(entry: &FileIndexEntry, selection: &FileSelection) -> bool {
+    // leaf columns of nested types are named by their dotted path
+    let has_column = |column: &str| {
+        entry.columns.iter().any(|c| {
+            c.name == column
+                || c.name
+                    .strip_prefix(column)
+                    .map(|rest| rest.starts_with('.'))
+                    .unwrap_or(false)
+        })
+    };
+    selection
+        .min_rows
+        .map(|min| entry.n_rows >= min)
+        .unwrap_or(true)
+        && selection
+            .max_rows
+            .map(|max| entry.n_rows <= max)
+            .unwrap_or(true)
+        && selection
+            .min_bytes
+            .map(|min| entry.n_bytes >= min)
+            .unwrap_or(true)
+        && selection.has_columns.iter().all(|c| has_column(c))
+        && !selection.missing_columns.iter().any(|c| has_column(c))
+        && selection
+            .schema
+            .as_ref()
+            .map(|schema| get_schema_hash(schema) == entry.schema_hash)
+            .unwrap_or(true)
+}
+
+/// return false if manifest statistics rule out every row of file
+pub fn index_entry_may_match(entry: &FileIndexEntry, filters: &[ColumnFilter]) -> bool {
+    filters.iter().all(|filter| {
+        let column = match entry.columns.iter().find(|c| c.name == filter.column) {
+            Some(column) => column,
+            None => return true,
+        };
+        let target = filter
+            .value
+            .as_deref()
+            .and_then(|value| column.stat_type.parse_filter_value(value));
+        if filter.value.is_some() && target.is_none() {
+            return true;
+        }
+        statistics_may_match(
+            filter.operator,
+            &column.stats,
+            target.as_ref(),
+            entry.n_rows,
+        )
+    })
+}
+
+/// skip files whose manifest statistics rule out every row, without reading their footers
+///
+/// paths missing from the manifest are kept. if every file is ruled out, the
+/// first file is kept so that outputs retain their schema
+pub fn prune_paths_by_index(
+    paths: Vec<PathBuf>,
+    entries: &[FileIndexEntry],
+    filters: &[ColumnFilter],
+) -> (Vec<PathBuf>, PruneSummary) {
+    let entries: HashMap<&Path, &FileIndexEntry> = entries
+        .iter()
+        .map(|entry| (entry.path.as_path(), entry))
+        .collect();
+    let mut summary = PruneSummary::default();
+    let first = paths.first().cloned();
+    let mut kept = Vec::new();
+    for path in paths.into_iter() {
+        match entries.get(path.as_path()) {
+            Some(entry) if !index_entry_may_match(entry, filters) => {
+                summary.n_files += 1;
+                summary.n_skipped_files += 1;
+                summary.n_row_groups += entry.n_row_groups;
+                summary.n_skipped_row_groups += entry.n_row_groups;
+                summary.n_rows += entry.n_rows;
+                summary.n_skipped_rows += entry.n_rows;
+            }
+            _ => kept.push(path),
+        }
+    }
+    if let (true, Some(first)) = (kept.is_empty(), first) {
+        if let Some(entry) = entries.get(first.as_path()) {
+            summary.n_files -= 1;
+            summary.n_skipped_files -= 1;
+            summary.n_row_groups -= entry.n_row_groups;
+            summary.n_skipped_row_groups -= entry.n_row_groups;
+            summary.n_rows -= entry.n_rows;
+            summary.n_skipped_rows -= entry.n_rows;
+        }
+        kept.push(first);
+    }
+    (kept, summary)
+}
+
+fn resolve_index_path(root: &Path, path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_absolute() || is_remote_path(&path) {
+        path
+    } else {
+        root.join(path)
+    }
+}
+
+fn get_index_root(index_path: &Path) -> Result<PathBuf, TblError> {
+    let absolute = std::path::absolute(index_path)?;
+    Ok(absolute
+        .parent()
+        .map(|parent| parent.to_path_buf())
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::parse_column_filter;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_build_index_dataframe() {
+        let path = PathBuf::from("blocks__00000000_to_00099999.parquet");
+        let entry = FileIndexEntry {
+            path: path.clone(),
+            n_bytes: 1000,
+            n_rows: 100000,
+            n_row_groups: 2,
+            schema_hash: "0123456789abcdef".to_string(),
+            columns: vec![IndexColumn {
+                name: "block_number".to_string(),
+                stat_type: StatType::Int,
+                stats: ColumnStatistics {
+                    min: Some(StatValue::Int(0)),
+                    max: Some(StatValue::Int(99999)),
+                    null_count: Some(0),
+                },
+            }],
+            range: parse_file_range(&path),
+        };
+        let df = build_index_dataframe(&[entry]).unwrap();
+        assert_eq!(df.height(), 1);
+        assert_eq!(
+            df.column("range_end").unwrap().u64().unwrap().get(0),
+            Some(99999)
+        );
+        let maxs = df.column("column_maxs").unwrap().list().unwrap();
+        let maxs = maxs.get_as_series(0).unwrap();
+        assert_eq!(maxs.str().unwrap().get(0), Some("99999"));
+        let int_maxs = df.column("column_int_maxs").unwrap().list().unwrap();
+        let int_maxs = int_maxs.get_as_series(0).unwrap();
+        assert_eq!(int_maxs.i64().unwrap().get(0), Some(99999));
+        assert_eq!(build_index_dataframe(&[]).unwrap().height(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_parquet_index() {
+        let dir = TempDir::new().unwrap();
+        let mut paths = Vec::new();
+        for (f, start) in [0u64, 1_000].into_iter().enumerate() {
+            let path = dir.path().join(format!("data_{}.parquet", f));
+            let mut df = df!(
+                "block_number" => (start..start + 1_000).collect::<Vec<u64>>(),
+                "price" => (start..start + 1_000).map(|i| i as f64 / 2.0).collect::<Vec<f64>>()
+            )
+            .unwrap();
+            let hashes: Vec<String> = (start..start + 1_000).map(|i| format!("{:x}", i)).collect();
+            df.with_column(Series::new("hash", hashes).cast(&DataType::Binary).unwrap())
+                .unwrap();
+            let file = std::fs::File::create(&path).unwrap();
+            ParquetWriter::new(file).finish(&mut df).unwrap();
+            paths.push(path);
+        }
+        let index_path = dir.path().join("index.parquet");
+        let written = write_parquet_index(&paths, &index_path).await.unwrap();
+        let entries = read_parquet_index(&index_path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].path, paths[1]);
+        assert_eq!(entries[1].schema_hash, written[1].schema_hash);
+        assert_eq!(entries[1].columns, written[1].columns);
+        assert_eq!(entries[1].columns[1].stat_type, StatType::Float);
+        assert_eq!(entries[1].columns[2].stat_type, StatType::Binary);
+
+        // schema hash matches the schema that polars reads
+        let schema = crate::parquet::get_parquet_schema(&paths[0]).await.unwrap();
+        let selection = FileSelection {
+            min_rows: Some(1_000),
+            has_columns: vec!["price".to_string()],
+            schema: Some(schema),
+            ..Default::default()
+        };
+        assert!(entries
+            .iter()
+            .all(|entry| index_entry_matches_selection(entry, &selection)));
+
+        let filters = vec![parse_column_filter("block_number>=1500").unwrap()];
+        let (kept, summary) = prune_paths_by_index(paths.clone(), &entries, &filters);
+        assert_eq!(kept, vec![paths[1].clone()]);
+        assert_eq!(summary.n_skipped_files, 1);
+        assert_eq!(summary.n_skipped_rows, 1_000);
+        let filters = vec![parse_column_filter("block_number>5000").unwrap()];
+        let (kept, summary) = prune_paths_by_index(paths.clone(), &entries, &filters);
+        assert_eq!(kept, vec![paths[0].clone()]);
+        assert_eq!(summary.n_files, 1);
+    }
+}
@@ -0,0 +1,166 @@
+use super::parquet_statistics::{get_column_chunk_statistics, ColumnStatistics};
+use super::parquet_summary::get_parquet_metadata_with_page_index;
+use crate::TblError;
+use parquet::file::metadata::{ColumnChunkMetaData, ParquetMetaData};
+use std::path::Path;
+
+/// layout of a parquet file, one entry per row group
+#[derive(Clone, Debug)]
+pub struct ParquetInspection {
+    /// number of rows
+    pub n_rows: u64,
+    /// application that wrote the file
+    pub created_by: Option<String>,
+    /// key-value metadata of footer
+    pub key_value_metadata: Vec<(String, Option<String>)>,
+    /// row groups of file
+    pub row_groups: Vec<RowGroupInspection>,
+}
+
+/// layout of a row group
+#[derive(Clone, Debug)]
+pub struct RowGroupInspection {
+    /// number of rows
+    pub n_rows: u64,
+    /// number of bytes after compression
+    pub n_bytes_compressed: u64,
+    /// number of bytes before compression
+    pub n_bytes_uncompressed: u64,
+    /// column chunks of row group
+    pub columns: Vec<ColumnChunkInspection>,
+}
+
+/// layout of a column chunk within a row group
+#[derive(Clone, Debug)]
+pub struct ColumnChunkInspection {
+    /// dotted path of column
+    pub name: String,
+    /// physical type of column
+    pub physical_type: String,
+    /// encodings used by pages of column chunk
+    pub encodings: Vec<String>,
+    /// compression codec
+    pub compression: String,
+    /// number of bytes after compression
+    pub n_bytes_compressed: u64,
+    /// number of bytes before compression
+    pub n_bytes_uncompressed: u64,
+    /// number of data pages, if file has an offset index
+    pub n_pages: Option<usize>,
+    /// number of bytes of dictionary page, if column chunk has one
+    pub dictionary_page_size: Option<u64>,
+    /// whether column chunk has statistics
+    pub has_statistics: bool,
+    /// min, max, and null count statistics
+    pub statistics: ColumnStatistics,
+    /// whether column chunk has a bloom filter
+    pub has_bloom_filter: bool,
+    /// whether column chunk has a column index
+    pub has_column_index: bool,
+    /// whether column chunk has an offset index
+    pub has_offset_index: bool,
+}
+
+/// inspect row groups and column chunks of parquet file
+pub async fn inspect_parquet_file(path: &Path) -> Result<ParquetInspection, TblError> {
+    let metadata = get_parquet_metadata_with_page_index(path).await?;
+    Ok(inspect_parquet_metadata(&metadata))
+}
+
+/// inspect row groups and column chunks of parquet metadata
+pub fn inspect_parquet_metadata(metadata: &ParquetMetaData) -> ParquetInspection {
+    let offset_index = metadata.offset_index();
+    let row_groups = metadata
+        .row_groups()
+        .iter()
+        .enumerate()
+        .map(|(rg, row_group)| RowGroupInspection {
+            n_rows: row_group.num_rows() as u64,
+            n_bytes_compressed: row_group.compressed_size() as u64,
+            n_bytes_uncompressed: row_group.total_byte_size() as u64,
+            columns: row_group
+                .columns()
+                .iter()
+                .enumerate()
+                .map(|(c, column)| {
+                    let n_pages = offset_index
+                        .and_then(|index| index.get(rg))
+                        .and_then(|row_group_index| row_group_index.get(c))
+                        .map(|locations| locations.len());
+                    inspect_column_chunk(column, n_pages)
+                })
+                .collect(),
+        })
+        .collect();
+    ParquetInspection {
+        n_rows: metadata.file_metadata().num_rows() as u64,
+        created_by: metadata.file_metadata().created_by().map(|s| s.to_string()),
+        key_value_metadata: super::parquet_metadata::get_key_value_metadata(metadata),
+        row_groups,
+    }
+}
+
+fn inspect_column_chunk(
+    column: &ColumnChunkMetaData,
+    n_pages: Option<usize>,
+) -> ColumnChunkInspection {
+    // dictionary page is written directly before the first data page
+    let dictionary_page_size = column
+        .dictionary_page_offset()
+        .map(|offset| (column.data_page_offset() - offset).max(0) as u64);
+    ColumnChunkInspection {
+        name: column.column_path().string(),
+        physical_type: column.column_type().to_string(),
+        encodings: column.encodings().iter().map(|e| e.to_string()).collect(),
+        compression: column.compression().to_string(),
+        n_bytes_compressed: column.compressed_size() as u64,
+        n_bytes_uncompressed: column.uncompressed_size() as u64,
+        n_pages,
+        dictionary_page_size,
+        has_statistics: column.statistics().is_some(),
+        statistics: get_column_chunk_statistics(column),
+        has_bloom_filter: column.bloom_filter_offset().is_some(),
+        has_column_index: column.column_index_offset().is_some(),
+        has_offset_index: column.offset_index_offset().is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_inspect_parquet_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        let df = df!(
+            "block_number" => (0u32..4_000).collect::<Vec<u32>>(),
+            "value" => (0u64..4_000).map(|i| i % 3).collect::<Vec<u64>>()
+        )
+        .unwrap();
+        crate::parquet::write_parquet_with_bloom_filters(
+            &df,
+            &path,
+            &[],
+            ParquetCompression::Snappy,
+            Some(1_000),
+        )
+        .unwrap();
+
+        let inspection = inspect_parquet_file(&path).await.unwrap();
+        assert_eq!(inspection.n_rows, 4_000);
+        assert_eq!(inspection.row_groups.len(), 4);
+        for row_group in inspection.row_groups.iter() {
+            assert_eq!(row_group.n_rows, 1_000);
+            assert_eq!(row_group.columns.len(), 2);
+            for column in row_group.columns.iter() {
+                assert_eq!(column.compression, "SNAPPY");
+                assert!(column.has_offset_index);
+                assert_eq!(column.n_pages, Some(1));
+            }
+        }
+        assert_eq!(inspection.row_groups[0].columns[0].name, "block_number");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use parquet::file::metadata::ParquetMetaData;
+use parquet::format::{FileMetaData, KeyValue};
+use parquet::thrift::TSerializable;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use thrift::protocol::TCompactOutputProtocol;
+
+/// key of the arrow schema that writers store in key-value metadata
+pub const ARROW_SCHEMA_KEY: &str = "ARROW:schema";
+
+/// get key-value metadata of parquet file footer
+pub fn get_key_value_metadata(metadata: &ParquetMetaData) -> Vec<(String, Option<String>)> {
+    metadata
+        .file_metadata()
+        .key_value_metadata()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| (entry.key.clone(), entry.value.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// get key-value metadata of parquet file
+pub async fn get_parquet_key_value_metadata(
+    path: &Path,
+) -> Result<Vec<(String, Option<String>)>, TblError> {
+    let metadata = super::parquet_summary::get_parquet_metadata(path).await?;
+    Ok(get_key_value_metadata(&metadata))
+}
+
+/// get key-value metadata of multiple parquet files
+pub async fn get_parquet_key_value_metadatas(
+    paths: &[PathBuf],
+) -> Result<Vec<Vec<(String, Option<String>)>>, TblError> {
+    stream::iter(paths)
+        .map(|path| get_parquet_key_value_metadata(path))
+        .buffered(10)
+        .collect::<Vec<Result<Vec<(String, Option<String>)>, TblError>>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// get key-value metadata entries shared by all files with identical values
+///
+/// the arrow schema is skipped because writers regenerate it for their output
+pub fn get_common_key_value_metadata(
+    per_file: &[Vec<(String, Option<String>)>],
+) -> Vec<(String, Option<String>)> {
+    let (first, rest) = match per_file.split_first() {
+        Some(split) => split,
+        None => return Vec::new(),
+    };
+    first
+        .iter()
+        .filter(|(key, _)| key != ARROW_SCHEMA_KEY)
+        .filter(|entry| rest.iter().all(|entries| entries.contains(entry)))
+        .cloned()
+        .collect()
+}
+
+/// set and drop key-value metadata entries of a parquet file in place
+///
+/// only the footer is rewritten, data pages are left untouched. entries in
+/// `set` replace existing entries with the same key, then keys in `drop` are removed
+pub fn update_parquet_key_value_metadata(
+    path: &Path,
+    set: &[(String, Option<String>)],
+    drop: &[String],
+) -> Result<(), TblError> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    let (mut metadata, footer_start) = read_parquet_footer(&mut file, path)?;
+
+    // edit footer
+    let mut entries = metadata.key_value_metadata.take().unwrap_or_default();
+    for (key, value) in set.iter() {
+        match entries.iter_mut().find(|entry| &entry.key == key) {
+            Some(entry) => entry.value = value.clone(),
+            None => entries.push(KeyValue::new(key.clone(), value.clone())),
+        }
+    }
+    entries.retain(|entry| !drop.contains(&entry.key));
+    metadata.key_value_metadata = if entries.is_empty() {
+        None
+    } else {
+        Some(entries)
+    };
+
+    write_parquet_footer(&mut file, &metadata, footer_start)
+}
+
+/// read thrift footer of parquet file, along with the offset where it starts
+pub(crate) fn read_parquet_footer(
+    file: &mut std::fs::File,
+    path: &Path,
+) -> Result<(FileMetaData, u64), TblError> {
+    let file_len = file.metadata()?.len();
+    let not_parquet = || TblError::InputError(format!("not a parquet file: {}", path.display()));
+    if file_len < 12 {
+        return Err(not_parquet());
+    }
+    let mut tail = [0u8; 8];
+    file.seek(SeekFrom::End(-8))?;
+    file.read_exact(&mut tail)?;
+    if &tail[4..] != b"PAR1" {
+        return Err(not_parquet());
+    }
+    let footer_len = u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]) as u64;
+    if footer_len + 12 > file_len {
+        return Err(not_parquet());
+    }
+    let footer_start = file_len - 8 - footer_len;
+    let mut footer = vec![0u8; footer_len as usize];
+    file.seek(SeekFrom::Start(footer_start))?;
+    file.read_exact(&mut footer)?;
+    let mut input = thrift::protocol::TCompactInputProtocol::new(footer.as_slice());
+    let metadata = FileMetaData::read_from_in_protocol(&mut input)?;
+    Ok((metadata, footer_start))
+}
+
+/// write thrift footer over the old footer that starts at `footer_start`
+pub(crate) fn write_parquet_footer(
+    file: &mut std::fs::File,
+    metadata: &FileMetaData,
+    footer_start: u64,
+) -> Result<(), TblError> {
+    let mut buffer = Vec::new();
+    {
+        let mut output = TCompactOutputProtocol::new(&mut buffer);
+        metadata.write_to_out_protocol(&mut output)?;
+    }
+    file.seek(SeekFrom::Start(footer_start))?;
+    file.write_all(&buffer)?;
+    file.write_all(&(buffer.len() as u32).to_le_bytes())?;
+    file.write_all(b"PAR1")?;
+    file.set_len(footer_start + buffer.len() as u64 + 8)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_update_parquet_key_value_metadata() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        let mut df = df!("block_number" => [1u32, 2, 3]).unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+
+        let set = vec![
+            ("source".to_string(), Some("node".to_string())),
+            ("tmp".to_string(), None),
+        ];
+        update_parquet_key_value_metadata(&path, &set, &["tmp".to_string()]).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        let entries = get_key_value_metadata(metadata);
+        assert!(entries.contains(&("source".to_string(), Some("node".to_string()))));
+        assert!(!entries.iter().any(|(key, _)| key == "tmp"));
+        assert_eq!(metadata.file_metadata().num_rows(), 3);
+    }
+
+    #[test]
+    fn test_get_common_key_value_metadata() {
+        let entry = |key: &str, value: &str| (key.to_string(), Some(value.to_string()));
+        let per_file = vec![
+            vec![
+                entry(ARROW_SCHEMA_KEY, "a"),
+                entry("source", "node"),
+                entry("version", "1"),
+            ],
+            vec![entry("version", "2"), entry("source", "node")],
+        ];
+        assert_eq!(
+            get_common_key_value_metadata(&per_file),
+            vec![entry("source", "node")]
+        );
+        assert!(get_common_key_value_metadata(&[]).is_empty());
+    }
+}
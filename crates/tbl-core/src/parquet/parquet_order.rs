@@ -0,0 +1,258 @@
+use crate::TblError;
+use arrow::array::ArrayRef;
+use arrow::row::{OwnedRow, RowConverter, SortField};
+use futures::stream::StreamExt;
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use parquet::arrow::ProjectionMask;
+use std::path::{Path, PathBuf};
+
+/// kind of key violation
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyViolationKind {
+    /// key is smaller than the key of the previous row
+    OutOfOrder,
+    /// key is equal to the key of the previous row
+    Duplicate,
+}
+
+impl std::fmt::Display for KeyViolationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyViolationKind::OutOfOrder => write!(f, "out of order"),
+            KeyViolationKind::Duplicate => write!(f, "duplicate"),
+        }
+    }
+}
+
+/// row whose key is out of order or duplicated
+#[derive(Clone, Debug)]
+pub struct KeyViolation {
+    /// kind of violation
+    pub kind: KeyViolationKind,
+    /// file containing the row
+    pub path: PathBuf,
+    /// row offset within file
+    pub row: u64,
+    /// file containing the previous row
+    pub previous_path: PathBuf,
+    /// row offset of the previous row within its file
+    pub previous_row: u64,
+}
+
+/// result of checking the ordering and uniqueness of a key across files
+#[derive(Clone, Debug, Default)]
+pub struct KeyCheck {
+    /// number of rows checked
+    pub n_rows: u64,
+    /// number of rows whose key is smaller than the previous key
+    pub n_out_of_order: u64,
+    /// number of rows whose key equals the previous key
+    pub n_duplicates: u64,
+    /// first violations found, in path order
+    pub violations: Vec<KeyViolation>,
+}
+
+impl KeyCheck {
+    /// return true if no violations were found
+    pub fn is_ok(&self) -> bool {
+        self.n_out_of_order == 0 && self.n_duplicates == 0
+    }
+}
+
+struct PreviousRow {
+    key: OwnedRow,
+    path: PathBuf,
+    row: u64,
+}
+
+/// check that key columns are sorted ascending within and across files in path order
+///
+/// files are streamed one batch at a time and only the key columns are decoded.
+/// if `unique` is set, rows with the same key as the previous row are reported as
+/// duplicates. at most `max_violations` violations are recorded, all are counted.
+pub async fn check_parquet_key_order(
+    paths: &[PathBuf],
+    key: &[String],
+    unique: bool,
+    max_violations: usize,
+) -> Result<KeyCheck, TblError> {
+    if key.is_empty() {
+        return Err(TblError::InputError("no key columns given".to_string()));
+    }
+
+    let mut check = KeyCheck::default();
+    let mut converter: Option<RowConverter> = None;
+    let mut previous: Option<PreviousRow> = None;
+    for path in paths.iter() {
+        check_file_key_order(
+            path,
+            key,
+            unique,
+            max_violations,
+            &mut converter,
+            &mut previous,
+            &mut check,
+        )
+        .await?;
+    }
+
+    Ok(check)
+}
+
+async fn check_file_key_order(
+    path: &Path,
+    key: &[String],
+    unique: bool,
+    max_violations: usize,
+    converter: &mut Option<RowConverter>,
+    previous: &mut Option<PreviousRow>,
+    check: &mut KeyCheck,
+) -> Result<(), TblError> {
+    let file = super::parquet_reader::open_parquet_file(path).await?;
+    let builder = ParquetRecordBatchStreamBuilder::new(file).await?;
+
+    // decode only key columns
+    let mut indices = Vec::new();
+    let mut sort_fields = Vec::new();
+    for name in key.iter() {
+        let index = builder.schema().index_of(name).map_err(|_| {
+            TblError::SchemaError(format!("column {} not found in {:?}", name, path))
+        })?;
+        indices.push(index);
+        sort_fields.push(SortField::new(
+            builder.schema().field(index).data_type().clone(),
+        ));
+    }
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+    let mut reader_stream = builder.with_projection(mask).build()?;
+    if converter.is_none() {
+        *converter = Some(RowConverter::new(sort_fields)?);
+    }
+    let converter = converter
+        .as_ref()
+        .ok_or_else(|| TblError::Error("row converter not initialized".to_string()))?;
+
+    let mut offset: u64 = 0;
+    while let Some(batch) = reader_stream.next().await {
+        let batch = batch?;
+        let columns = key
+            .iter()
+            .map(|name| {
+                batch.column_by_name(name).cloned().ok_or_else(|| {
+                    TblError::SchemaError(format!("column {} not found in {:?}", name, path))
+                })
+            })
+            .collect::<Result<Vec<ArrayRef>, TblError>>()?;
+        let rows = converter.convert_columns(&columns)?;
+
+        for i in 0..rows.num_rows() {
+            let row = rows.row(i);
+            let (previous_key, previous_path, previous_row) = if i > 0 {
+                (rows.row(i - 1), path, offset + i as u64 - 1)
+            } else if let Some(previous) = previous.as_ref() {
+                (previous.key.row(), previous.path.as_path(), previous.row)
+            } else {
+                continue;
+            };
+            let kind = match row.cmp(&previous_key) {
+                std::cmp::Ordering::Less => KeyViolationKind::OutOfOrder,
+                std::cmp::Ordering::Equal if unique => KeyViolationKind::Duplicate,
+                _ => continue,
+            };
+            match kind {
+                KeyViolationKind::OutOfOrder => check.n_out_of_order += 1,
+                KeyViolationKind::Duplicate => check.n_duplicates += 1,
+            }
+            if check.violations.len() < max_violations {
+                check.violations.push(KeyViolation {
+                    kind,
+                    path: path.to_path_buf(),
+                    row: offset + i as u64,
+                    previous_path: previous_path.to_path_buf(),
+                    previous_row,
+                });
+            }
+        }
+        if rows.num_rows() > 0 {
+            *previous = Some(PreviousRow {
+                key: rows.row(rows.num_rows() - 1).owned(),
+                path: path.to_path_buf(),
+                row: offset + rows.num_rows() as u64 - 1,
+            });
+        }
+
+        offset += batch.num_rows() as u64;
+    }
+    check.n_rows += offset;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    fn write_test_file(dir: &Path, name: &str, blocks: &[u32], indices: &[u32]) -> PathBuf {
+        let path = dir.join(name);
+        let mut df = df!("block_number" => blocks, "log_index" => indices).unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file)
+            .with_row_group_size(Some(2))
+            .finish(&mut df)
+            .unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_check_parquet_key_order() {
+        let dir = TempDir::new().unwrap();
+        let paths = vec![
+            write_test_file(dir.path(), "a.parquet", &[1, 1, 2, 3], &[0, 1, 0, 0]),
+            write_test_file(dir.path(), "b.parquet", &[3, 4, 4, 5], &[0, 0, 0, 1]),
+        ];
+        let key = vec!["block_number".to_string(), "log_index".to_string()];
+
+        // duplicate key spans the boundary between files
+        let check = check_parquet_key_order(&paths, &key, true, 10)
+            .await
+            .unwrap();
+        assert_eq!(check.n_rows, 8);
+        assert_eq!(check.n_out_of_order, 0);
+        assert_eq!(check.n_duplicates, 2);
+        assert_eq!(check.violations[0].path, paths[1]);
+        assert_eq!(check.violations[0].row, 0);
+        assert_eq!(check.violations[0].previous_path, paths[0]);
+        assert_eq!(check.violations[0].previous_row, 3);
+        assert_eq!(check.violations[1].row, 2);
+
+        let check = check_parquet_key_order(&paths, &key, false, 10)
+            .await
+            .unwrap();
+        assert!(check.is_ok());
+
+        // files out of path order
+        let reversed = vec![paths[1].clone(), paths[0].clone()];
+        let check = check_parquet_key_order(&reversed, &key, false, 1)
+            .await
+            .unwrap();
+        assert_eq!(check.n_out_of_order, 1);
+        assert_eq!(check.violations.len(), 1);
+        assert_eq!(check.violations[0].kind, KeyViolationKind::OutOfOrder);
+        assert_eq!(check.violations[0].path, paths[0]);
+    }
+
+    #[tokio::test]
+    async fn test_check_parquet_key_order_missing_column() {
+        let dir = TempDir::new().unwrap();
+        let paths = vec![write_test_file(dir.path(), "a.parquet", &[1], &[0])];
+        let key = vec!["missing".to_string()];
+        assert!(check_parquet_key_order(&paths, &key, true, 10)
+            .await
+            .is_err());
+        assert!(check_parquet_key_order(&paths, &[], true, 10)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use super::parquet_metadata::{read_parquet_footer, write_parquet_footer, ARROW_SCHEMA_KEY};
+use crate::TblError;
+use arrow::datatypes::{Field, Schema};
+use arrow::ipc::writer::{IpcDataGenerator, IpcWriteOptions};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use parquet::arrow::parquet_to_arrow_schema;
+use parquet::basic::Compression;
+use parquet::file::metadata::ParquetMetaData;
+use parquet::format::{KeyValue, SortingColumn};
+use parquet::schema::types::SchemaDescriptor;
+use polars::prelude::ParquetCompression;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// column that rows of a parquet file are sorted by
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortedColumn {
+    /// name of column
+    pub name: String,
+    /// whether values are in descending order
+    pub descending: bool,
+    /// whether nulls come before values
+    pub nulls_first: bool,
+}
+
+/// physical layout of a parquet file that rewrites can carry over
+#[derive(Clone, Debug, Default)]
+pub struct ParquetLayout {
+    /// compression codec of column chunks
+    pub compression: Option<Compression>,
+    /// number of rows per row group, taken from the first row group
+    pub row_group_size: Option<usize>,
+    /// columns that rows are sorted by, outermost first
+    pub sorting_columns: Vec<SortedColumn>,
+    /// names of columns in file order
+    pub column_names: Vec<String>,
+    /// arrow field metadata of each column that has any
+    pub field_metadata: Vec<(String, HashMap<String, String>)>,
+}
+
+/// get layout of parquet file
+pub async fn get_parquet_layout(path: &Path) -> Result<ParquetLayout, TblError> {
+    let metadata = super::parquet_summary::get_parquet_metadata(path).await?;
+    get_layout_from_metadata(&metadata)
+}
+
+/// get layout from parquet metadata
+pub fn get_layout_from_metadata(metadata: &ParquetMetaData) -> Result<ParquetLayout, TblError> {
+    let file_metadata = metadata.file_metadata();
+    let schema_descr = file_metadata.schema_descr();
+    let first_row_group = metadata.row_groups().first();
+
+    // sorting columns are stored per row group as leaf column indices
+    let sorting_columns = first_row_group
+        .and_then(|row_group| row_group.sorting_columns())
+        .map(|columns| {
+            columns
+                .iter()
+                .filter(|column| (column.column_idx as usize) < schema_descr.num_columns())
+                .map(|column| SortedColumn {
+                    name: schema_descr
+                        .column(column.column_idx as usize)
+                        .path()
+                        .string(),
+                    descending: column.descending,
+                    nulls_first: column.nulls_first,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let arrow_schema = parquet_to_arrow_schema(schema_descr, file_metadata.key_value_metadata())?;
+    Ok(ParquetLayout {
+        compression: first_row_group
+            .and_then(|row_group| row_group.columns().first())
+            .map(|column| column.compression()),
+        row_group_size: first_row_group.map(|row_group| row_group.num_rows() as usize),
+        sorting_columns,
+        column_names: arrow_schema
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect(),
+        field_metadata: arrow_schema
+            .fields()
+            .iter()
+            .filter(|field| !field.metadata().is_empty())
+            .map(|field| (field.name().clone(), field.metadata().clone()))
+            .collect(),
+    })
+}
+
+/// convert parquet compression codec into the polars codec that writes it
+///
+/// compression levels are not stored in parquet files, so default levels are used
+pub fn get_polars_compression(compression: Compression) -> Option<ParquetCompression> {
+    match compression {
+        Compression::UNCOMPRESSED => Some(ParquetCompression::Uncompressed),
+        Compression::SNAPPY => Some(ParquetCompression::Snappy),
+        Compression::GZIP(_) => Some(ParquetCompression::Gzip(None)),
+        Compression::BROTLI(_) => Some(ParquetCompression::Brotli(None)),
+        Compression::ZSTD(_) => Some(ParquetCompression::Zstd(None)),
+        Compression::LZ4_RAW => Some(ParquetCompression::Lz4Raw),
+        Compression::LZO | Compression::LZ4 => None,
+    }
+}
+
+/// convert polars compression codec into the parquet codec that writes it
+///
+/// polars keeps compression levels private, so default levels are used
+pub fn get_parquet_compression(compression: ParquetCompression) -> Option<Compression> {
+    match compression {
+        ParquetCompression::Uncompressed => Some(Compression::UNCOMPRESSED),
+        ParquetCompression::Snappy => Some(Compression::SNAPPY),
+        ParquetCompression::Gzip(_) => Some(Compression::GZIP(Default::default())),
+        ParquetCompression::Brotli(_) => Some(Compression::BROTLI(Default::default())),
+        ParquetCompression::Zstd(_) => Some(Compression::ZSTD(Default::default())),
+        ParquetCompression::Lz4Raw => Some(Compression::LZ4_RAW),
+        ParquetCompression::Lzo => None,
+    }
+}
+
+/// set sorting columns and arrow field metadata of a parquet file in place
+///
+/// like `update_parquet_key_value_metadata`, only the footer is rewritten.
+/// sorting stops at the first column missing from the file, and field metadata
+/// is only added to columns that do not already have their own
+pub fn update_parquet_layout(
+    path: &Path,
+    sorting_columns: &[SortedColumn],
+    field_metadata: &[(String, HashMap<String, String>)],
+) -> Result<(), TblError> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    let (mut metadata, footer_start) = read_parquet_footer(&mut file, path)?;
+
+    // set sorting columns of each row group
+    if !sorting_columns.is_empty() {
+        for row_group in metadata.row_groups.iter_mut() {
+            let paths: Vec<Option<String>> = row_group
+                .columns
+                .iter()
+                .map(|column| {
+                    column
+                        .meta_data
+                        .as_ref()
+                        .map(|m| m.path_in_schema.join("."))
+                })
+                .collect();
+            let mut sorting = Vec::new();
+            for column in sorting_columns.iter() {
+                match paths
+                    .iter()
+                    .position(|p| p.as_deref() == Some(column.name.as_str()))
+                {
+                    Some(idx) => sorting.push(SortingColumn::new(
+                        idx as i32,
+                        column.descending,
+                        column.nulls_first,
+                    )),
+                    None => break,
+                }
+            }
+            row_group.sorting_columns = if sorting.is_empty() {
+                None
+            } else {
+                Some(sorting)
+            };
+        }
+    }
+
+    // merge field metadata into the stored arrow schema
+    if !field_metadata.is_empty() {
+        let root = parquet::schema::types::from_thrift(&metadata.schema)?;
+        let schema_descr = SchemaDescriptor::new(root);
+        let schema = parquet_to_arrow_schema(&schema_descr, metadata.key_value_metadata.as_ref())?;
+        let mut changed = false;
+        let fields: Vec<Field> = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let preserved = field_metadata
+                    .iter()
+                    .find(|(name, _)| name == field.name())
+                    .map(|(_, metadata)| metadata);
+                match preserved {
+                    Some(preserved) if field.metadata().is_empty() => {
+                        changed = true;
+                        field.as_ref().clone().with_metadata(preserved.clone())
+                    }
+                    _ => field.as_ref().clone(),
+                }
+            })
+            .collect();
+        if changed {
+            let encoded = encode_arrow_schema(&Schema::new(fields));
+            let mut entries = metadata.key_value_metadata.take().unwrap_or_default();
+            match entries
+                .iter_mut()
+                .find(|entry| entry.key == ARROW_SCHEMA_KEY)
+            {
+                Some(entry) => entry.value = Some(encoded),
+                None => entries.push(KeyValue::new(ARROW_SCHEMA_KEY.to_string(), encoded)),
+            }
+            metadata.key_value_metadata = Some(entries);
+        }
+    }
+
+    write_parquet_footer(&mut file, &metadata, footer_start)
+}
+
+/// encode arrow schema the way parquet writers store it in key-value metadata
+fn encode_arrow_schema(schema: &Schema) -> String {
+    let options = IpcWriteOptions::default();
+    let encoded = IpcDataGenerator::default().schema_to_bytes(schema, &options);
+
+    // legacy ipc framing, a continuation marker followed by the message length
+    let mut bytes = Vec::with_capacity(encoded.ipc_message.len() + 8);
+    bytes.extend_from_slice(&[255u8, 255, 255, 255]);
+    bytes.extend_from_slice(&(encoded.ipc_message.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&encoded.ipc_message);
+    BASE64_STANDARD.encode(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_update_parquet_layout() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        let mut df = df!(
+            "block_number" => [1u32, 2, 3],
+            "value" => [5u64, 4, 6]
+        )
+        .unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+
+        let sorting_columns = vec![
+            SortedColumn {
+                name: "block_number".to_string(),
+                descending: false,
+                nulls_first: true,
+            },
+            SortedColumn {
+                name: "missing".to_string(),
+                descending: false,
+                nulls_first: true,
+            },
+        ];
+        let unit = HashMap::from([("unit".to_string(), "wei".to_string())]);
+        let field_metadata = vec![("value".to_string(), unit.clone())];
+        update_parquet_layout(&path, &sorting_columns, &field_metadata).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let layout = get_layout_from_metadata(reader.metadata()).unwrap();
+        assert_eq!(layout.sorting_columns, sorting_columns[..1].to_vec());
+        assert_eq!(layout.field_metadata, field_metadata);
+        assert_eq!(layout.column_names, vec!["block_number", "value"]);
+        assert_eq!(layout.row_group_size, Some(3));
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+    }
+}
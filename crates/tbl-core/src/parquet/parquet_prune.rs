@@ -0,0 +1,635 @@
+use super::parquet_sample::push_selector;
+use super::parquet_statistics::{
+    get_column_chunk_statistics, get_page_index_statistics, get_stat_type, ColumnStatistics,
+    StatValue,
+};
+use crate::filters::{ColumnFilter, FilterOperator};
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use parquet::arrow::arrow_reader::{ArrowReaderOptions, RowSelection, RowSelector};
+use parquet::arrow::async_reader::{AsyncFileReader, ParquetRecordBatchStreamBuilder};
+use parquet::arrow::ProjectionMask;
+use parquet::basic::Type;
+use parquet::data_type::ByteArray;
+use parquet::file::metadata::{ColumnChunkMetaData, ParquetMetaData, RowGroupMetaData};
+use polars::prelude::{
+    col, concat, DataFrame, DataType, Expr, IdxSize, IntoLazy, LazyFrame, UnionArgs,
+};
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// default number of parquet files read at once when selecting or reading rows
+pub const DEFAULT_MAX_CONCURRENT: usize = 16;
+
+/// counts of files, row groups, and rows ruled out by statistics
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    /// number of files considered
+    pub n_files: u64,
+    /// number of files skipped
+    pub n_skipped_files: u64,
+    /// number of row groups considered
+    pub n_row_groups: u64,
+    /// number of row groups that cannot match
+    pub n_skipped_row_groups: u64,
+    /// number of rows considered
+    pub n_rows: u64,
+    /// number of rows in skipped row groups and pages
+    pub n_skipped_rows: u64,
+}
+
+impl PruneSummary {
+    /// add counts of another summary
+    pub fn add(&mut self, other: &PruneSummary) {
+        self.n_files += other.n_files;
+        self.n_skipped_files += other.n_skipped_files;
+        self.n_row_groups += other.n_row_groups;
+        self.n_skipped_row_groups += other.n_skipped_row_groups;
+        self.n_rows += other.n_rows;
+        self.n_skipped_rows += other.n_skipped_rows;
+    }
+}
+
+/// row groups and rows of a parquet file that may pass filters
+#[derive(Clone, Debug)]
+pub struct ParquetRowSelection {
+    /// path of file
+    pub path: PathBuf,
+    /// number of row groups in file
+    pub n_row_groups: u64,
+    /// number of rows in file
+    pub n_rows: u64,
+    /// row groups that may contain matching rows
+    pub row_groups: Vec<usize>,
+    /// rows of file covered by each selected row group
+    pub row_group_rows: Vec<Range<u64>>,
+    /// rows of the selected row groups that may match, none if all of them may
+    pub selection: Option<RowSelection>,
+    /// number of rows that may match
+    pub n_selected_rows: u64,
+}
+
+/// return false if no row of a chunk with these statistics can pass filter
+///
+/// null filters are never used for pruning because writers may omit null counts
+pub fn statistics_may_match(
+    operator: FilterOperator,
+    stats: &ColumnStatistics,
+    target: Option<&StatValue>,
+    n_rows: u64,
+) -> bool {
+    let all_null = n_rows > 0 && stats.null_count == Some(n_rows);
+    let (min, max, target) = match operator {
+        FilterOperator::IsNull => return true,
+        FilterOperator::IsNotNull => return !all_null,
+        _ if all_null => return false,
+        _ => match (&stats.min, &stats.max, target) {
+            (Some(min), Some(max), Some(target)) => (min, max, target),
+            _ => return true,
+        },
+    };
+    let (lo, hi) = match (min.partial_cmp(target), max.partial_cmp(target)) {
+        (Some(lo), Some(hi)) => (lo, hi),
+        _ => return true,
+    };
+    match operator {
+        FilterOperator::Eq => lo != Ordering::Greater && hi != Ordering::Less,
+        FilterOperator::NotEq => !(lo == Ordering::Equal && hi == Ordering::Equal),
+        FilterOperator::Gt => hi == Ordering::Greater,
+        FilterOperator::GtEq => hi != Ordering::Less,
+        FilterOperator::Lt => lo == Ordering::Less,
+        FilterOperator::LtEq => lo != Ordering::Greater,
+        FilterOperator::IsNull | FilterOperator::IsNotNull => true,
+    }
+}
+
+/// convert raw filter value into a statistic value comparable with a column chunk
+///
+/// returns none when the column type is not understood, e.g. decimals or dates
+fn parse_filter_stat_value(value: &str, column: &ColumnChunkMetaData) -> Option<StatValue> {
+    get_stat_type(column.column_descr()).parse_filter_value(value)
+}
+
+/// return false if no row of row group can pass every filter
+pub fn row_group_may_match(row_group: &RowGroupMetaData, filters: &[ColumnFilter]) -> bool {
+    let n_rows = row_group.num_rows() as u64;
+    filters.iter().all(|filter| {
+        let column = match row_group
+            .columns()
+            .iter()
+            .find(|chunk| chunk.column_path().string() == filter.column)
+        {
+            Some(column) => column,
+            None => return true,
+        };
+        let target = filter
+            .value
+            .as_deref()
+            .and_then(|value| parse_filter_stat_value(value, column));
+        if filter.value.is_some() && target.is_none() {
+            return true;
+        }
+        let stats = get_column_chunk_statistics(column);
+        statistics_may_match(filter.operator, &stats, target.as_ref(), n_rows)
+    })
+}
+
+/// return false if bloom filters rule out the value of an equality filter
+///
+/// only row groups whose column chunks were written with bloom filters are checked
+async fn bloom_filters_may_match(
+    builder: &mut ParquetRecordBatchStreamBuilder<Box<dyn AsyncFileReader>>,
+    rg: usize,
+    filters: &[ColumnFilter],
+) -> Result<bool, TblError> {
+    let metadata = builder.metadata().clone();
+    let row_group = metadata.row_group(rg);
+    for filter in filters.iter() {
+        if filter.operator != FilterOperator::Eq {
+            continue;
+        }
+        let (c, column) = match row_group
+            .columns()
+            .iter()
+            .enumerate()
+            .find(|(_, chunk)| chunk.column_path().string() == filter.column)
+        {
+            Some((c, column)) if column.bloom_filter_offset().is_some() => (c, column),
+            _ => continue,
+        };
+        let target = match filter
+            .value
+            .as_deref()
+            .and_then(|value| parse_filter_stat_value(value, column))
+        {
+            Some(target) => target,
+            None => continue,
+        };
+        let bloom_filter = match builder.get_row_group_column_bloom_filter(rg, c).await? {
+            Some(bloom_filter) => bloom_filter,
+            None => continue,
+        };
+
+        // bloom filters hash the physical value, so integers are truncated to their width
+        let may_contain = match (column.column_type(), target) {
+            (Type::INT32, StatValue::Int(value)) => bloom_filter.check(&(value as i32)),
+            (Type::INT64, StatValue::Int(value)) => bloom_filter.check(&(value as i64)),
+            (Type::BYTE_ARRAY, StatValue::Bytes(value)) => {
+                bloom_filter.check(&ByteArray::from(value))
+            }
+            _ => true,
+        };
+        if !may_contain {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// select rows of a row group whose pages may pass every filter
+///
+/// pages are ruled out using the min and max values of the column index, and
+/// none is returned if no filtered column has a page index
+pub fn page_row_selectors(
+    metadata: &ParquetMetaData,
+    rg: usize,
+    filters: &[ColumnFilter],
+) -> Option<Vec<RowSelector>> {
+    let column_index = metadata.column_index()?.get(rg)?;
+    let offset_index = metadata.offset_index()?.get(rg)?;
+    let row_group = metadata.row_group(rg);
+    let n_rows = row_group.num_rows() as usize;
+    let mut selection: Option<RowSelection> = None;
+    for filter in filters.iter() {
+        let c = match row_group
+            .columns()
+            .iter()
+            .position(|chunk| chunk.column_path().string() == filter.column)
+        {
+            Some(c) => c,
+            None => continue,
+        };
+        let column = row_group.column(c);
+        let (index, locations) = match (column_index.get(c), offset_index.get(c)) {
+            (Some(index), Some(locations)) => (index, locations),
+            _ => continue,
+        };
+        let pages = match get_page_index_statistics(index, column) {
+            Some(pages) if pages.len() == locations.len() => pages,
+            _ => continue,
+        };
+        let target = filter
+            .value
+            .as_deref()
+            .and_then(|value| parse_filter_stat_value(value, column));
+        if filter.value.is_some() && target.is_none() {
+            continue;
+        }
+
+        let mut selectors = Vec::new();
+        for (p, (stats, location)) in pages.iter().zip(locations.iter()).enumerate() {
+            let start = location.first_row_index as usize;
+            let end = match locations.get(p + 1) {
+                Some(next) => next.first_row_index as usize,
+                None => n_rows,
+            };
+            let n_page_rows = end.saturating_sub(start);
+            let keep =
+                statistics_may_match(filter.operator, stats, target.as_ref(), n_page_rows as u64);
+            push_selector(&mut selectors, !keep, n_page_rows);
+        }
+        let page_selection = RowSelection::from(selectors);
+        selection = Some(match selection {
+            Some(selection) => selection.intersection(&page_selection),
+            None => page_selection,
+        });
+    }
+    selection.map(Vec::from)
+}
+
+/// select row groups and pages of a parquet file that may pass every filter
+///
+/// row groups are ruled out by their statistics and bloom filters, and pages
+/// within the remaining row groups by their page index
+pub async fn select_parquet_rows(
+    path: &Path,
+    filters: &[ColumnFilter],
+) -> Result<ParquetRowSelection, TblError> {
+    let file = super::parquet_reader::open_parquet_file(path).await?;
+    let options = ArrowReaderOptions::new().with_page_index(true);
+    let mut builder = ParquetRecordBatchStreamBuilder::new_with_options(file, options).await?;
+    let metadata = builder.metadata().clone();
+
+    let mut row_groups = Vec::new();
+    let mut row_group_rows = Vec::new();
+    let mut row_offset = 0;
+    let mut selectors = Vec::new();
+    let mut n_selected_rows = 0;
+    let mut n_row_group_rows = 0;
+    for (rg, row_group) in metadata.row_groups().iter().enumerate() {
+        let n_rows = row_group.num_rows() as usize;
+        let start = row_offset;
+        row_offset += n_rows as u64;
+        if !row_group_may_match(row_group, filters)
+            || !bloom_filters_may_match(&mut builder, rg, filters).await?
+        {
+            continue;
+        }
+        let rg_selectors = page_row_selectors(&metadata, rg, filters)
+            .unwrap_or_else(|| vec![RowSelector::select(n_rows)]);
+        let n_selected: usize = rg_selectors
+            .iter()
+            .filter(|selector| !selector.skip)
+            .map(|selector| selector.row_count)
+            .sum();
+        if n_selected == 0 {
+            continue;
+        }
+        row_groups.push(rg);
+        row_group_rows.push(start..row_offset);
+        for selector in rg_selectors.into_iter() {
+            push_selector(&mut selectors, selector.skip, selector.row_count);
+        }
+        n_selected_rows += n_selected as u64;
+        n_row_group_rows += n_rows as u64;
+    }
+
+    Ok(ParquetRowSelection {
+        path: path.to_path_buf(),
+        n_row_groups: metadata.num_row_groups() as u64,
+        n_rows: metadata.file_metadata().num_rows() as u64,
+        row_groups,
+        row_group_rows,
+        selection: if n_selected_rows < n_row_group_rows {
+            Some(RowSelection::from(selectors))
+        } else {
+            None
+        },
+        n_selected_rows,
+    })
+}
+
+/// select row groups and pages of parquet files that may pass every filter
+///
+/// footers of up to `max_concurrent` files are read at once. files without
+/// any selected rows are dropped, except that if every file is
+/// ruled out the first file is kept so that outputs retain their schema
+pub async fn select_parquets_rows(
+    paths: &[PathBuf],
+    filters: &[ColumnFilter],
+    max_concurrent: usize,
+) -> Result<(Vec<ParquetRowSelection>, PruneSummary), TblError> {
+    let selections = stream::iter(paths.iter())
+        .map(|path| select_parquet_rows(path, filters))
+        .buffered(max_concurrent.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<ParquetRowSelection>, TblError>>()?;
+
+    let mut summary = PruneSummary {
+        n_files: paths.len() as u64,
+        ..Default::default()
+    };
+    for selection in selections.iter() {
+        summary.n_row_groups += selection.n_row_groups;
+        summary.n_skipped_row_groups += selection.n_row_groups - selection.row_groups.len() as u64;
+        summary.n_rows += selection.n_rows;
+        summary.n_skipped_rows += selection.n_rows - selection.n_selected_rows;
+    }
+    let first = selections.first().cloned();
+    let mut kept: Vec<ParquetRowSelection> = selections
+        .into_iter()
+        .filter(|selection| selection.n_selected_rows > 0)
+        .collect();
+    if let (true, Some(first)) = (kept.is_empty(), first) {
+        kept.push(first);
+    }
+    summary.n_skipped_files = summary.n_files - kept.len() as u64;
+
+    Ok((kept, summary))
+}
+
+/// skip files whose row group statistics and bloom filters rule out every row
+///
+/// if every file is ruled out, the first file is kept so that outputs retain their schema
+pub async fn prune_paths_by_statistics(
+    paths: Vec<PathBuf>,
+    filters: &[ColumnFilter],
+) -> Result<(Vec<PathBuf>, PruneSummary), TblError> {
+    if filters.is_empty() || paths.is_empty() {
+        let summary = PruneSummary {
+            n_files: paths.len() as u64,
+            ..Default::default()
+        };
+        return Ok((paths, summary));
+    }
+    let (selections, summary) =
+        select_parquets_rows(&paths, filters, DEFAULT_MAX_CONCURRENT).await?;
+    let kept = selections
+        .into_iter()
+        .map(|selection| selection.path)
+        .collect();
+    Ok((kept, summary))
+}
+
+/// read the selected rows of a parquet file, only reading `columns` if given
+pub async fn read_selected_rows(
+    selection: &ParquetRowSelection,
+    columns: Option<&[String]>,
+) -> Result<DataFrame, TblError> {
+    let file = super::parquet_reader::open_parquet_file(&selection.path).await?;
+    let builder = ParquetRecordBatchStreamBuilder::new(file).await?;
+    let (builder, schema) = match columns {
+        Some(columns) => {
+            let indices: Vec<usize> = builder
+                .schema()
+                .fields()
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| columns.contains(field.name()))
+                .map(|(i, _)| i)
+                .collect();
+            let schema = Arc::new(builder.schema().project(&indices)?);
+            let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+            (builder.with_projection(mask), schema)
+        }
+        None => {
+            let schema = builder.schema().clone();
+            (builder, schema)
+        }
+    };
+
+    let mut batches = Vec::new();
+    if !selection.row_groups.is_empty() {
+        let builder = builder.with_row_groups(selection.row_groups.clone());
+        let builder = match &selection.selection {
+            Some(row_selection) => builder.with_row_selection(row_selection.clone()),
+            None => builder,
+        };
+        let mut reader_stream = builder.build()?;
+        while let Some(batch) = reader_stream.next().await {
+            batches.push(batch?);
+        }
+    }
+
+    super::parquet_scan::record_batches_to_dataframe(schema, &batches)
+}
+
+/// create lazy frame of the selected rows of parquet files
+///
+/// only the selected row groups and pages are read, and only `columns` if
+/// given along with hive partition columns. each file is filtered by
+/// `predicate` before files are combined, so that rows read from selected
+/// pages are not all held in memory at once. rows that do not pass the filters
+/// used for selection still need to be filtered afterwards if no predicate is given
+pub async fn create_selected_lazyframe(
+    selections: &[ParquetRowSelection],
+    hive_roots: &[PathBuf],
+    columns: Option<&[String]>,
+    predicate: Option<Expr>,
+) -> Result<LazyFrame, TblError> {
+    let paths: Vec<PathBuf> = selections.iter().map(|s| s.path.clone()).collect();
+    let hive_schema = super::parquet_scan::get_hive_schema(&paths, hive_roots);
+
+    // hive columns stored in files are read so that they are not replaced by directory values
+    let columns: Option<Vec<String>> = columns.map(|columns| {
+        let mut columns = columns.to_vec();
+        for (column, _) in hive_schema.iter() {
+            if !columns.contains(column) {
+                columns.push(column.clone());
+            }
+        }
+        columns
+    });
+
+    let lfs = stream::iter(selections.iter())
+        .map(|selection| {
+            create_selected_file_lazyframe(
+                selection,
+                hive_roots,
+                &hive_schema,
+                columns.as_deref(),
+                predicate.clone(),
+            )
+        })
+        .buffered(DEFAULT_MAX_CONCURRENT)
+        .collect::<Vec<Result<LazyFrame, TblError>>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<LazyFrame>, TblError>>()?;
+    Ok(concat(lfs, UnionArgs::default())?)
+}
+
+async fn create_selected_file_lazyframe(
+    selection: &ParquetRowSelection,
+    hive_roots: &[PathBuf],
+    hive_schema: &[(String, DataType)],
+    columns: Option<&[String]>,
+    predicate: Option<Expr>,
+) -> Result<LazyFrame, TblError> {
+    // whole row groups are scanned by polars, pages are read and filtered right away
+    let (mut lf, read) = match &selection.selection {
+        None => (scan_selected_row_groups(selection)?, false),
+        Some(_) => (read_selected_rows(selection, columns).await?.lazy(), true),
+    };
+    let file_schema = lf.schema()?;
+    let mut lf = match columns {
+        Some(columns) => lf.select(
+            columns
+                .iter()
+                .filter(|column| file_schema.contains(column))
+                .map(|column| col(column))
+                .collect::<Vec<_>>(),
+        ),
+        None => lf,
+    };
+    let file_schema = lf.schema()?;
+    let lf = lf.with_columns(super::parquet_scan::get_hive_column_exprs(
+        &selection.path,
+        hive_roots,
+        hive_schema,
+        &file_schema,
+    ));
+    match (predicate, read) {
+        (Some(predicate), true) => Ok(lf.filter(predicate).collect()?.lazy()),
+        (Some(predicate), false) => Ok(lf.filter(predicate)),
+        (None, _) => Ok(lf),
+    }
+}
+
+/// scan the selected row groups of a parquet file, one slice per run of adjacent row groups
+fn scan_selected_row_groups(selection: &ParquetRowSelection) -> Result<LazyFrame, TblError> {
+    let lf = LazyFrame::scan_parquet(&selection.path, super::parquet_scan::get_file_scan_args())?;
+    let mut runs: Vec<Range<u64>> = Vec::new();
+    for rows in selection.row_group_rows.iter() {
+        match runs.last_mut() {
+            Some(run) if run.end == rows.start => run.end = rows.end,
+            _ => runs.push(rows.clone()),
+        }
+    }
+    if runs.is_empty() {
+        return Ok(lf.limit(0));
+    }
+    let lfs = runs
+        .into_iter()
+        .map(|run| {
+            lf.clone()
+                .slice(run.start as i64, (run.end - run.start) as IdxSize)
+        })
+        .collect::<Vec<_>>();
+    Ok(concat(lfs, UnionArgs::default())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::parse_column_filter;
+    use polars::prelude::{df, lit, ParquetWriter};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_create_selected_lazyframe() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        let mut df = df!(
+            "block_number" => (0u32..10_000).collect::<Vec<u32>>(),
+            "value" => (0u32..10_000).map(|i| i % 7).collect::<Vec<u32>>()
+        )
+        .unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file)
+            .with_row_group_size(Some(1_000))
+            .finish(&mut df)
+            .unwrap();
+
+        // whole row groups, then pages once the file has a page index
+        let filters = vec![parse_column_filter("block_number>=8500").unwrap()];
+        let columns = vec!["block_number".to_string()];
+        for page_index in [false, true] {
+            if page_index {
+                crate::parquet::write_parquet_bloom_filters(&path, &path, &[])
+                    .await
+                    .unwrap();
+            }
+            let (selections, summary) =
+                select_parquets_rows(std::slice::from_ref(&path), &filters, 1)
+                    .await
+                    .unwrap();
+            assert_eq!(summary.n_skipped_row_groups, 8);
+            let predicate = col("block_number").gt_eq(lit(8500u32));
+            let df = create_selected_lazyframe(&selections, &[], Some(&columns), Some(predicate))
+                .await
+                .unwrap()
+                .collect()
+                .unwrap();
+            assert_eq!(df.shape(), (1_500, 1));
+        }
+    }
+
+    #[test]
+    fn test_statistics_may_match() {
+        let stats = ColumnStatistics {
+            min: Some(StatValue::Int(100)),
+            max: Some(StatValue::Int(200)),
+            null_count: Some(0),
+        };
+        let check = |operator, target: i128| {
+            statistics_may_match(operator, &stats, Some(&StatValue::Int(target)), 10)
+        };
+        assert!(check(FilterOperator::Eq, 150));
+        assert!(!check(FilterOperator::Eq, 201));
+        assert!(check(FilterOperator::GtEq, 200));
+        assert!(!check(FilterOperator::Gt, 200));
+        assert!(!check(FilterOperator::Lt, 100));
+        assert!(check(FilterOperator::LtEq, 100));
+        assert!(check(FilterOperator::NotEq, 100));
+
+        let constant = ColumnStatistics {
+            min: Some(StatValue::Int(5)),
+            max: Some(StatValue::Int(5)),
+            null_count: Some(0),
+        };
+        let five = StatValue::Int(5);
+        assert!(!statistics_may_match(
+            FilterOperator::NotEq,
+            &constant,
+            Some(&five),
+            10
+        ));
+
+        let unknown = ColumnStatistics::default();
+        assert!(statistics_may_match(
+            FilterOperator::Eq,
+            &unknown,
+            Some(&five),
+            10
+        ));
+
+        let all_null = ColumnStatistics {
+            min: None,
+            max: None,
+            null_count: Some(10),
+        };
+        assert!(!statistics_may_match(
+            FilterOperator::Eq,
+            &all_null,
+            Some(&five),
+            10
+        ));
+        assert!(!statistics_may_match(
+            FilterOperator::IsNotNull,
+            &all_null,
+            None,
+            10
+        ));
+        assert!(statistics_may_match(
+            FilterOperator::IsNull,
+            &all_null,
+            None,
+            10
+        ));
+    }
+}
@@ -0,0 +1,18 @@
+use crate::filesystem::{get_object_store, is_remote_path};
+use crate::TblError;
+use parquet::arrow::async_reader::{AsyncFileReader, ParquetObjectReader};
+use std::path::Path;
+
+/// open parquet file for async reading, from local disk or object storage
+///
+/// remote files are read with range requests, so reading metadata only fetches
+/// the footer of each file
+pub async fn open_parquet_file(path: &Path) -> Result<Box<dyn AsyncFileReader>, TblError> {
+    if is_remote_path(path) {
+        let (store, location) = get_object_store(path)?;
+        let meta = store.head(&location).await?;
+        Ok(Box::new(ParquetObjectReader::new(store, meta)))
+    } else {
+        Ok(Box::new(tokio::fs::File::open(path).await?))
+    }
+}
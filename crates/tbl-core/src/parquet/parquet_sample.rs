@@ -0,0 +1,325 @@
+use crate::TblError;
+use futures::stream::{self, StreamExt};
+use parquet::arrow::arrow_reader::{RowSelection, RowSelector};
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use polars::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// number of rows to sample
+#[derive(Clone, Copy, Debug)]
+pub enum SampleSize {
+    /// sample a fixed number of rows
+    Rows(u64),
+    /// sample a fraction of rows
+    Fraction(f64),
+}
+
+impl SampleSize {
+    /// number of rows to sample from a population of `n_total` rows
+    pub fn n_rows(&self, n_total: u64) -> u64 {
+        match self {
+            SampleSize::Rows(n) => std::cmp::min(*n, n_total),
+            SampleSize::Fraction(fraction) => {
+                let fraction = fraction.clamp(0.0, 1.0);
+                ((n_total as f64) * fraction).round() as u64
+            }
+        }
+    }
+}
+
+/// small deterministic random number generator (splitmix64)
+struct SampleRng(u64);
+
+impl SampleRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// draw sorted row offsets uniformly without replacement from `0..n_total`
+pub fn sample_row_offsets(n_total: u64, n_sample: u64, seed: u64) -> Vec<u64> {
+    if n_sample >= n_total {
+        return (0..n_total).collect();
+    }
+
+    // floyd's algorithm, draws exactly n_sample distinct offsets
+    let mut rng = SampleRng(seed);
+    let mut selected: HashSet<u64> = HashSet::with_capacity(n_sample as usize);
+    for j in (n_total - n_sample)..n_total {
+        let t = rng.below(j + 1);
+        if !selected.insert(t) {
+            selected.insert(j);
+        }
+    }
+    let mut offsets: Vec<u64> = selected.into_iter().collect();
+    offsets.sort_unstable();
+    offsets
+}
+
+/// split sorted global row offsets into row offsets local to each file
+pub fn split_row_offsets(offsets: &[u64], row_counts: &[u64]) -> Vec<Vec<u64>> {
+    let mut per_file = Vec::with_capacity(row_counts.len());
+    let mut start = 0;
+    let mut i = 0;
+    for row_count in row_counts.iter() {
+        let end = start + row_count;
+        let mut local = Vec::new();
+        while i < offsets.len() && offsets[i] < end {
+            local.push(offsets[i] - start);
+            i += 1;
+        }
+        per_file.push(local);
+        start = end;
+    }
+    per_file
+}
+
+/// append selector to selectors, merging it into the last selector of the same kind
+pub(crate) fn push_selector(selectors: &mut Vec<RowSelector>, skip: bool, row_count: usize) {
+    if row_count == 0 {
+        return;
+    }
+    match selectors.last_mut() {
+        Some(last) if last.skip == skip => last.row_count += row_count,
+        _ => selectors.push(if skip {
+            RowSelector::skip(row_count)
+        } else {
+            RowSelector::select(row_count)
+        }),
+    }
+}
+
+/// read specific rows of a parquet file
+///
+/// only row groups that contain at least one of the sorted row offsets are read,
+/// and pages that contain none of the rows are skipped where possible
+pub async fn read_parquet_rows(path: &Path, rows: &[u64]) -> Result<DataFrame, TblError> {
+    let file = super::parquet_reader::open_parquet_file(path).await?;
+    let builder = ParquetRecordBatchStreamBuilder::new(file).await?;
+    let schema = builder.schema().clone();
+
+    // choose row groups from metadata and build selection relative to them
+    let mut row_groups = Vec::new();
+    let mut selectors = Vec::new();
+    let mut rg_start: u64 = 0;
+    let mut i = 0;
+    for (rg_index, rg) in builder.metadata().row_groups().iter().enumerate() {
+        let rg_end = rg_start + rg.num_rows() as u64;
+        let first = i;
+        while i < rows.len() && rows[i] < rg_end {
+            i += 1;
+        }
+        if i > first {
+            row_groups.push(rg_index);
+            let mut position = rg_start;
+            for row in rows[first..i].iter() {
+                push_selector(&mut selectors, true, (row - position) as usize);
+                push_selector(&mut selectors, false, 1);
+                position = row + 1;
+            }
+            push_selector(&mut selectors, true, (rg_end - position) as usize);
+        }
+        rg_start = rg_end;
+    }
+
+    let mut batches = Vec::new();
+    if !row_groups.is_empty() {
+        let mut reader_stream = builder
+            .with_row_groups(row_groups)
+            .with_row_selection(RowSelection::from(selectors))
+            .build()?;
+        while let Some(batch) = reader_stream.next().await {
+            batches.push(batch?);
+        }
+    }
+
+    super::parquet_scan::record_batches_to_dataframe(schema, &batches)
+}
+
+/// create lazy frame of rows sampled uniformly across parquet files
+///
+/// rows are drawn using the row counts in each file's metadata, so only the row
+/// groups containing sampled rows are read
+pub async fn create_sampled_lazyframe(
+    paths: &[PathBuf],
+    hive_roots: &[PathBuf],
+    sample: SampleSize,
+    seed: u64,
+) -> Result<LazyFrame, TblError> {
+    let path_refs: Vec<&Path> = paths.iter().map(|path| path.as_path()).collect();
+    let row_counts = super::parquet_summary::get_parquet_row_counts(&path_refs).await?;
+    let n_total: u64 = row_counts.iter().sum();
+    let offsets = sample_row_offsets(n_total, sample.n_rows(n_total), seed);
+    read_sampled_rows(paths, hive_roots, &row_counts, &offsets).await
+}
+
+/// create lazy frame of rows sampled separately for each value of a column
+///
+/// only the stratification column is scanned in full, the sampled rows are then
+/// read from the row groups that contain them
+pub async fn create_stratified_lazyframe(
+    paths: &[PathBuf],
+    hive_roots: &[PathBuf],
+    column: &str,
+    sample: SampleSize,
+    seed: u64,
+) -> Result<LazyFrame, TblError> {
+    let path_refs: Vec<&Path> = paths.iter().map(|path| path.as_path()).collect();
+    let row_counts = super::parquet_summary::get_parquet_row_counts(&path_refs).await?;
+
+    // gather row offsets of each stratum, in order of first appearance so that
+    // each stratum gets the same seed on every run
+    let row_index = "__tbl_row_index";
+    let paths_vec = paths.to_vec();
+    let hive_roots_vec = hive_roots.to_vec();
+    let column_name = column.to_string();
+    let strata = tokio::task::spawn_blocking(move || {
+        super::parquet_scan::create_lazyframe(&paths_vec, &hive_roots_vec)?
+            .select([col(&column_name)])
+            .with_row_index(row_index, None)
+            .group_by_stable([col(&column_name)])
+            .agg([col(row_index)])
+            .collect()
+            .map_err(TblError::from)
+    })
+    .await??;
+
+    // sample within each stratum
+    let mut offsets = Vec::new();
+    for (s, stratum) in strata.column(row_index)?.list()?.into_iter().enumerate() {
+        let stratum = match stratum {
+            Some(stratum) => stratum,
+            None => continue,
+        };
+        let stratum_offsets: Vec<u64> = stratum
+            .idx()?
+            .into_no_null_iter()
+            .map(|offset| offset as u64)
+            .collect();
+        let n_stratum = stratum_offsets.len() as u64;
+        let stratum_seed = seed.wrapping_add(s as u64);
+        for i in sample_row_offsets(n_stratum, sample.n_rows(n_stratum), stratum_seed) {
+            offsets.push(stratum_offsets[i as usize]);
+        }
+    }
+    offsets.sort_unstable();
+
+    read_sampled_rows(paths, hive_roots, &row_counts, &offsets).await
+}
+
+async fn read_sampled_rows(
+    paths: &[PathBuf],
+    hive_roots: &[PathBuf],
+    row_counts: &[u64],
+    offsets: &[u64],
+) -> Result<LazyFrame, TblError> {
+    let per_file = split_row_offsets(offsets, row_counts);
+    let selected: Vec<(&PathBuf, Vec<u64>)> = paths
+        .iter()
+        .zip(per_file)
+        .filter(|(_, rows)| !rows.is_empty())
+        .collect();
+    if selected.is_empty() {
+        return match paths.first() {
+            Some(path) => Ok(super::parquet_scan::create_lazyframe(
+                std::slice::from_ref(path),
+                hive_roots,
+            )?
+            .slice(0, 0)),
+            None => Err(TblError::InputError("no files to sample".to_string())),
+        };
+    }
+
+    let dfs = stream::iter(selected.iter())
+        .map(|(path, rows)| async move { read_parquet_rows(path, rows).await })
+        .buffered(10)
+        .collect::<Vec<Result<DataFrame, TblError>>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<DataFrame>, TblError>>()?;
+
+    // add hive partition columns of each file
+    let hive_schema = super::parquet_scan::get_hive_schema(paths, hive_roots);
+    let lfs: Vec<LazyFrame> = dfs
+        .into_iter()
+        .zip(selected.iter())
+        .map(|(df, (path, _))| {
+            let exprs = super::parquet_scan::get_hive_column_exprs(
+                path,
+                hive_roots,
+                &hive_schema,
+                &df.schema(),
+            );
+            df.lazy().with_columns(exprs)
+        })
+        .collect();
+    Ok(concat(lfs, UnionArgs::default())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_row_offsets() {
+        let offsets = sample_row_offsets(1000, 10, 42);
+        assert_eq!(offsets.len(), 10);
+        assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+        assert!(offsets.iter().all(|offset| *offset < 1000));
+        assert_eq!(offsets, sample_row_offsets(1000, 10, 42));
+        assert_eq!(sample_row_offsets(5, 10, 42), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_stratified_sample_is_reproducible() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        let n_rows = 10_000u32;
+        let mut df = df!(
+            "row" => (0..n_rows).collect::<Vec<u32>>(),
+            "stratum" => (0..n_rows).map(|i| format!("s{}", i % 50)).collect::<Vec<String>>()
+        )
+        .unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+
+        let paths = vec![path];
+        let sample = |seed| {
+            let paths = paths.clone();
+            async move {
+                create_stratified_lazyframe(&paths, &[], "stratum", SampleSize::Rows(3), seed)
+                    .await
+                    .unwrap()
+                    .collect()
+                    .unwrap()
+            }
+        };
+        let first = sample(7).await;
+        assert_eq!(first.height(), 150);
+        for _ in 0..3 {
+            assert!(first.equals(&sample(7).await));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sample_without_files() {
+        assert!(create_sampled_lazyframe(&[], &[], SampleSize::Rows(3), 7)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_split_row_offsets() {
+        let per_file = split_row_offsets(&[0, 4, 5, 12], &[5, 0, 10]);
+        assert_eq!(per_file, vec![vec![0, 4], vec![], vec![0, 7]]);
+    }
+}
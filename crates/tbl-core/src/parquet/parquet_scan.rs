@@ -1,10 +1,277 @@
+use crate::filesystem::{get_hive_columns, parse_hive_partitions, split_hive_segment};
 use crate::TblError;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use polars::io::HiveOptions;
 use polars::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// create lazy frame by scanning input paths
-pub fn create_lazyframe(paths: &[PathBuf]) -> Result<LazyFrame, TblError> {
-    let scan_args = polars::prelude::ScanArgsParquet::default();
-    let arc_paths = Arc::from(paths.to_vec().into_boxed_slice());
-    Ok(LazyFrame::scan_parquet_files(arc_paths, scan_args)?)
+///
+/// hive partitions in directory names below `hive_roots`, such as `year=2024/`,
+/// are added as columns. a file column of the same name as a partition is kept
+/// as stored rather than replaced by the directory value
+pub fn create_lazyframe(paths: &[PathBuf], hive_roots: &[PathBuf]) -> Result<LazyFrame, TblError> {
+    let arc_paths: Arc<[PathBuf]> = Arc::from(paths.to_vec().into_boxed_slice());
+    let lf = LazyFrame::scan_parquet_files(arc_paths.clone(), get_file_scan_args())?;
+    let hive_schema = get_hive_schema(paths, hive_roots);
+    if hive_schema.is_empty() {
+        return Ok(lf);
+    }
+
+    // let polars read the partitions when its parsing of paths agrees with ours
+    let file_schema = lf.clone().schema()?;
+    if let Some((schema, above_root)) =
+        get_polars_hive_schema(paths, hive_roots, &hive_schema, &file_schema)
+    {
+        let scan_args = ScanArgsParquet {
+            hive_options: HiveOptions {
+                enabled: Some(true),
+                hive_start_idx: 0,
+                schema: Some(Arc::new(schema)),
+                try_parse_dates: false,
+            },
+            ..Default::default()
+        };
+        let lf = LazyFrame::scan_parquet_files(arc_paths, scan_args)?;
+        return Ok(lf.drop(above_root));
+    }
+
+    let mut lfs = Vec::new();
+    for path in paths.iter() {
+        let mut lf = LazyFrame::scan_parquet(path, get_file_scan_args())?;
+        let file_schema = lf.schema()?;
+        lfs.push(lf.with_columns(get_hive_column_exprs(
+            path,
+            hive_roots,
+            &hive_schema,
+            &file_schema,
+        )));
+    }
+    Ok(concat(lfs, UnionArgs::default())?)
+}
+
+/// get scan arguments that read only the columns stored in files
+///
+/// polars otherwise parses every `key=value` directory of a path into a column
+/// that replaces any file column of the same name
+pub fn get_file_scan_args() -> ScanArgsParquet {
+    ScanArgsParquet {
+        hive_options: HiveOptions {
+            enabled: Some(false),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// get hive schema for polars to parse the partitions of paths, and the
+/// partition columns above the input roots that it parses but should be dropped
+///
+/// polars parses every `key=value` directory of explicitly listed files and lets
+/// partitions replace file columns, so this is none unless all paths share the
+/// same partition keys and none of them collide with a file column
+fn get_polars_hive_schema(
+    paths: &[PathBuf],
+    hive_roots: &[PathBuf],
+    hive_schema: &[(String, DataType)],
+    file_schema: &Schema,
+) -> Option<(Schema, Vec<String>)> {
+    let hive_keys: Vec<&str> = hive_schema.iter().map(|(key, _)| key.as_str()).collect();
+    let first_keys = get_polars_hive_keys(paths.first()?)?;
+    for path in paths.iter() {
+        let keys: Vec<String> = parse_hive_partitions(path, hive_roots)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        if keys != hive_keys || get_polars_hive_keys(path)? != first_keys {
+            return None;
+        }
+    }
+
+    let mut schema = Schema::with_capacity(first_keys.len());
+    let mut above_root = Vec::new();
+    for key in first_keys.iter() {
+        if file_schema.contains(key) || schema.contains(key) {
+            return None;
+        }
+        let dtype = match hive_schema.iter().find(|(column, _)| column == key) {
+            Some((_, dtype)) => dtype.clone(),
+            None => {
+                above_root.push(key.clone());
+                DataType::String
+            }
+        };
+        schema.with_column(key.clone().into(), dtype);
+    }
+    Some((schema, above_root))
+}
+
+/// get keys of every `key=value` directory of path, as polars parses them
+fn get_polars_hive_keys(path: &Path) -> Option<Vec<String>> {
+    let mut segments: Vec<&str> = path.to_str()?.split('/').collect();
+    segments.pop();
+    Some(
+        segments
+            .into_iter()
+            .filter_map(split_hive_segment)
+            .map(|(key, _)| key.to_string())
+            .collect(),
+    )
+}
+
+/// get hive partition columns of paths and their types
+///
+/// columns whose non-null values all parse as integers are Int64, other
+/// columns are String
+pub fn get_hive_schema(paths: &[PathBuf], hive_roots: &[PathBuf]) -> Vec<(String, DataType)> {
+    let partitions: Vec<Vec<(String, Option<String>)>> = paths
+        .iter()
+        .map(|path| parse_hive_partitions(path, hive_roots))
+        .collect();
+    get_hive_columns(paths, hive_roots)
+        .into_iter()
+        .map(|column| {
+            let all_ints = partitions
+                .iter()
+                .flatten()
+                .filter(|(key, _)| key == &column)
+                .filter_map(|(_, value)| value.as_ref())
+                .all(|value| value.parse::<i64>().is_ok());
+            let dtype = if all_ints {
+                DataType::Int64
+            } else {
+                DataType::String
+            };
+            (column, dtype)
+        })
+        .collect()
+}
+
+/// get hive partition columns that `create_lazyframe` adds from directory names
+///
+/// partitions that a file stores as columns itself are left out, so dropping
+/// these columns before rewriting a file keeps all of its own data
+pub async fn get_added_hive_columns(
+    paths: &[PathBuf],
+    hive_roots: &[PathBuf],
+) -> Result<Vec<String>, TblError> {
+    let mut columns = get_hive_columns(paths, hive_roots);
+    for path in paths.iter() {
+        if columns.is_empty() {
+            break;
+        }
+        let file_schema = super::parquet_summary::get_parquet_schema(path).await?;
+        columns.retain(|column| !file_schema.contains(column));
+    }
+    Ok(columns)
+}
+
+/// get expressions that add the hive partition values of a path as columns
+///
+/// partitions stored as columns of the file itself are left as they are
+pub fn get_hive_column_exprs(
+    path: &Path,
+    hive_roots: &[PathBuf],
+    hive_schema: &[(String, DataType)],
+    file_schema: &Schema,
+) -> Vec<Expr> {
+    let partitions = parse_hive_partitions(path, hive_roots);
+    hive_schema
+        .iter()
+        .filter(|(column, _)| !file_schema.contains(column))
+        .map(|(column, dtype)| {
+            let value = match partitions.iter().find(|(key, _)| key == column) {
+                Some((_, Some(value))) => lit(value.as_str()),
+                _ => lit(NULL),
+            };
+            value.cast(dtype.clone()).alias(column)
+        })
+        .collect()
+}
+
+/// convert arrow record batches into a polars dataframe
+pub fn record_batches_to_dataframe(
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+) -> Result<DataFrame, TblError> {
+    let mut buffer = Vec::new();
+    {
+        let mut writer = arrow::ipc::writer::FileWriter::try_new(&mut buffer, &schema)?;
+        for batch in batches.iter() {
+            writer.write(batch)?;
+        }
+        writer.finish()?;
+    }
+    Ok(IpcReader::new(std::io::Cursor::new(buffer)).finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_parquet(path: &Path, df: &mut DataFrame) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let file = std::fs::File::create(path).unwrap();
+        ParquetWriter::new(file).finish(df).unwrap();
+    }
+
+    #[test]
+    fn test_create_lazyframe_with_hive_partitions() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("env=prod/data");
+        let paths = vec![
+            root.join("year=2023/part-0.parquet"),
+            root.join("year=2024/part-0.parquet"),
+            root.join("year=__HIVE_DEFAULT_PARTITION__/part-0.parquet"),
+        ];
+        for (i, path) in paths.iter().enumerate() {
+            write_parquet(path, &mut df!("value" => [i as u32]).unwrap());
+        }
+
+        let df = create_lazyframe(&paths, &[root])
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(df.get_column_names(), vec!["value", "year"]);
+        let year = df.column("year").unwrap().i64().unwrap();
+        assert_eq!(
+            year.into_iter().collect::<Vec<_>>(),
+            vec![Some(2023), Some(2024), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_keeps_stored_hive_column() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        let path = root.join("year=2024/part-0.parquet");
+        let mut df = df!(
+            "year" => [2023i64, 2024],
+            "value" => [1u32, 2]
+        )
+        .unwrap();
+        write_parquet(&path, &mut df);
+
+        // the stored column is read as is, and is not dropped before rewriting
+        let paths = vec![path];
+        let roots = vec![root.clone()];
+        assert!(get_added_hive_columns(&paths, &roots)
+            .await
+            .unwrap()
+            .is_empty());
+        let lf = create_lazyframe(&paths, &roots).unwrap();
+        let mut rewritten = lf
+            .drop(get_added_hive_columns(&paths, &roots).await.unwrap())
+            .collect()
+            .unwrap();
+        let output_path = root.join("output/part-0.parquet");
+        write_parquet(&output_path, &mut rewritten);
+        let output = LazyFrame::scan_parquet(&output_path, ScanArgsParquet::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert!(output.equals(&df));
+    }
 }
@@ -0,0 +1,364 @@
+use crate::filters::parse_filter_int;
+use parquet::basic::{ConvertedType, Type};
+use parquet::file::metadata::{ColumnChunkMetaData, ParquetMetaData, RowGroupMetaData};
+use parquet::file::page_index::index::{Index, PageIndex};
+use parquet::file::statistics::Statistics;
+use parquet::schema::types::ColumnDescriptor;
+
+/// value of a min or max column statistic
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub enum StatValue {
+    /// boolean value
+    Boolean(bool),
+    /// signed or unsigned integer value
+    Int(i128),
+    /// floating point value
+    Float(f64),
+    /// string or binary value
+    Bytes(Vec<u8>),
+}
+
+impl std::fmt::Display for StatValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatValue::Boolean(value) => write!(f, "{}", value),
+            StatValue::Int(value) => write!(f, "{}", value),
+            StatValue::Float(value) => write!(f, "{}", value),
+            StatValue::Bytes(value) => match std::str::from_utf8(value) {
+                Ok(value) => write!(f, "{}", value),
+                Err(_) => write!(f, "0x{}", hex::encode(value)),
+            },
+        }
+    }
+}
+
+/// kind of values that the statistics of a column hold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatType {
+    /// booleans
+    Boolean,
+    /// plain signed or unsigned integers
+    Int,
+    /// floating point numbers
+    Float,
+    /// utf8 strings
+    String,
+    /// raw binary
+    Binary,
+    /// logical types such as dates and decimals whose statistics are not compared
+    Other,
+}
+
+impl StatType {
+    /// name of stat type
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatType::Boolean => "boolean",
+            StatType::Int => "int",
+            StatType::Float => "float",
+            StatType::String => "string",
+            StatType::Binary => "binary",
+            StatType::Other => "other",
+        }
+    }
+
+    /// parse name of stat type
+    pub fn from_name(name: &str) -> StatType {
+        match name {
+            "boolean" => StatType::Boolean,
+            "int" => StatType::Int,
+            "float" => StatType::Float,
+            "string" => StatType::String,
+            "binary" => StatType::Binary,
+            _ => StatType::Other,
+        }
+    }
+
+    /// parse raw filter value into a statistic value comparable with this type
+    ///
+    /// returns none for types whose statistics are not used for pruning
+    pub fn parse_filter_value(&self, value: &str) -> Option<StatValue> {
+        match self {
+            StatType::Int => parse_filter_int(value).map(StatValue::Int),
+            StatType::String => Some(StatValue::Bytes(value.as_bytes().to_vec())),
+            StatType::Binary => value
+                .strip_prefix("0x")
+                .and_then(|hex_value| hex::decode(hex_value).ok())
+                .map(StatValue::Bytes),
+            StatType::Boolean | StatType::Float | StatType::Other => None,
+        }
+    }
+}
+
+/// get kind of statistic values of a column
+pub fn get_stat_type(descr: &ColumnDescriptor) -> StatType {
+    match (descr.physical_type(), descr.converted_type()) {
+        (Type::BOOLEAN, _) => StatType::Boolean,
+        (
+            Type::INT32 | Type::INT64,
+            ConvertedType::NONE
+            | ConvertedType::INT_8
+            | ConvertedType::INT_16
+            | ConvertedType::INT_32
+            | ConvertedType::INT_64
+            | ConvertedType::UINT_8
+            | ConvertedType::UINT_16
+            | ConvertedType::UINT_32
+            | ConvertedType::UINT_64,
+        ) => StatType::Int,
+        (Type::FLOAT | Type::DOUBLE, _) => StatType::Float,
+        (Type::BYTE_ARRAY, ConvertedType::UTF8) => StatType::String,
+        (Type::BYTE_ARRAY, ConvertedType::NONE) => StatType::Binary,
+        _ => StatType::Other,
+    }
+}
+
+/// min, max, and null count statistics of a column
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ColumnStatistics {
+    /// minimum value
+    pub min: Option<StatValue>,
+    /// maximum value
+    pub max: Option<StatValue>,
+    /// number of null values
+    pub null_count: Option<u64>,
+}
+
+impl ColumnStatistics {
+    /// combine statistics of two chunks of the same column
+    ///
+    /// min and max are only kept if both sides have them, because a missing
+    /// statistic means that the true bound is unknown
+    pub fn merge(&self, other: &ColumnStatistics) -> ColumnStatistics {
+        let min = match (&self.min, &other.min) {
+            (Some(lhs), Some(rhs)) => Some(if rhs < lhs { rhs.clone() } else { lhs.clone() }),
+            _ => None,
+        };
+        let max = match (&self.max, &other.max) {
+            (Some(lhs), Some(rhs)) => Some(if rhs > lhs { rhs.clone() } else { lhs.clone() }),
+            _ => None,
+        };
+        let null_count = match (self.null_count, other.null_count) {
+            (Some(lhs), Some(rhs)) => Some(lhs + rhs),
+            _ => None,
+        };
+        ColumnStatistics {
+            min,
+            max,
+            null_count,
+        }
+    }
+}
+
+/// get statistics of a column chunk
+pub fn get_column_chunk_statistics(column: &ColumnChunkMetaData) -> ColumnStatistics {
+    let stats = match column.statistics() {
+        Some(stats) => stats,
+        None => return ColumnStatistics::default(),
+    };
+    let null_count = Some(stats.null_count());
+    if !stats.has_min_max_set() {
+        return ColumnStatistics {
+            min: None,
+            max: None,
+            null_count,
+        };
+    }
+
+    let unsigned = is_unsigned(column);
+    let (min, max) = match stats {
+        Statistics::Boolean(s) => (
+            Some(StatValue::Boolean(*s.min())),
+            Some(StatValue::Boolean(*s.max())),
+        ),
+        Statistics::Int32(s) if unsigned => (
+            Some(StatValue::Int(*s.min() as u32 as i128)),
+            Some(StatValue::Int(*s.max() as u32 as i128)),
+        ),
+        Statistics::Int32(s) => (
+            Some(StatValue::Int(*s.min() as i128)),
+            Some(StatValue::Int(*s.max() as i128)),
+        ),
+        Statistics::Int64(s) if unsigned => (
+            Some(StatValue::Int(*s.min() as u64 as i128)),
+            Some(StatValue::Int(*s.max() as u64 as i128)),
+        ),
+        Statistics::Int64(s) => (
+            Some(StatValue::Int(*s.min() as i128)),
+            Some(StatValue::Int(*s.max() as i128)),
+        ),
+        Statistics::Int96(_) => (None, None),
+        Statistics::Float(s) => (
+            Some(StatValue::Float(*s.min() as f64)),
+            Some(StatValue::Float(*s.max() as f64)),
+        ),
+        Statistics::Double(s) => (
+            Some(StatValue::Float(*s.min())),
+            Some(StatValue::Float(*s.max())),
+        ),
+        Statistics::ByteArray(s) => (
+            Some(StatValue::Bytes(s.min().data().to_vec())),
+            Some(StatValue::Bytes(s.max().data().to_vec())),
+        ),
+        Statistics::FixedLenByteArray(s) => (
+            Some(StatValue::Bytes(s.min().data().to_vec())),
+            Some(StatValue::Bytes(s.max().data().to_vec())),
+        ),
+    };
+
+    ColumnStatistics {
+        min,
+        max,
+        null_count,
+    }
+}
+
+/// get statistics of each page of a column chunk from its column index
+///
+/// returns none if the column chunk has no column index or its type is not understood
+pub fn get_page_index_statistics(
+    index: &Index,
+    column: &ColumnChunkMetaData,
+) -> Option<Vec<ColumnStatistics>> {
+    fn pages<T>(
+        indexes: &[PageIndex<T>],
+        convert: impl Fn(&T) -> StatValue,
+    ) -> Vec<ColumnStatistics> {
+        indexes
+            .iter()
+            .map(|page| ColumnStatistics {
+                min: page.min.as_ref().map(&convert),
+                max: page.max.as_ref().map(&convert),
+                null_count: page.null_count.map(|n| n as u64),
+            })
+            .collect()
+    }
+
+    let unsigned = is_unsigned(column);
+    match index {
+        Index::BOOLEAN(native) => Some(pages(&native.indexes, |v| StatValue::Boolean(*v))),
+        Index::INT32(native) if unsigned => Some(pages(&native.indexes, |v| {
+            StatValue::Int(*v as u32 as i128)
+        })),
+        Index::INT32(native) => Some(pages(&native.indexes, |v| StatValue::Int(*v as i128))),
+        Index::INT64(native) if unsigned => Some(pages(&native.indexes, |v| {
+            StatValue::Int(*v as u64 as i128)
+        })),
+        Index::INT64(native) => Some(pages(&native.indexes, |v| StatValue::Int(*v as i128))),
+        Index::FLOAT(native) => Some(pages(&native.indexes, |v| StatValue::Float(*v as f64))),
+        Index::DOUBLE(native) => Some(pages(&native.indexes, |v| StatValue::Float(*v))),
+        Index::BYTE_ARRAY(native) => Some(pages(&native.indexes, |v| {
+            StatValue::Bytes(v.data().to_vec())
+        })),
+        Index::FIXED_LEN_BYTE_ARRAY(native) => Some(pages(&native.indexes, |v| {
+            StatValue::Bytes(v.data().to_vec())
+        })),
+        Index::NONE | Index::INT96(_) => None,
+    }
+}
+
+/// return true if integer values of column are stored as unsigned
+fn is_unsigned(column: &ColumnChunkMetaData) -> bool {
+    matches!(
+        column.column_descr().converted_type(),
+        ConvertedType::UINT_8
+            | ConvertedType::UINT_16
+            | ConvertedType::UINT_32
+            | ConvertedType::UINT_64
+    )
+}
+
+/// get statistics of a column within a row group
+pub fn get_row_group_column_statistics(
+    row_group: &RowGroupMetaData,
+    column: &str,
+) -> Option<ColumnStatistics> {
+    row_group
+        .columns()
+        .iter()
+        .find(|chunk| chunk.column_path().string() == column)
+        .map(get_column_chunk_statistics)
+}
+
+/// get statistics of a column across all row groups of a parquet file
+pub fn get_parquet_column_statistics(
+    metadata: &ParquetMetaData,
+    column: &str,
+) -> Option<ColumnStatistics> {
+    let mut total: Option<ColumnStatistics> = None;
+    for row_group in metadata.row_groups() {
+        let stats = get_row_group_column_statistics(row_group, column)?;
+        total = Some(match total {
+            Some(total) => total.merge(&stats),
+            None => stats,
+        });
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use polars::prelude::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_get_parquet_column_statistics() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.parquet");
+        let mut df = df!(
+            "block_number" => [3_000_000_000u32, 3_000_000_001, 7, 9],
+            "name" => [Some("b"), None, Some("a"), Some("c")],
+            "price" => [1.5f64, -2.0, 0.0, 4.25]
+        )
+        .unwrap();
+        let file = std::fs::File::create(&path).unwrap();
+        ParquetWriter::new(file)
+            .with_row_group_size(Some(2))
+            .finish(&mut df)
+            .unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), 2);
+
+        // unsigned integers above i32::MAX keep their value across row groups
+        let stats = get_parquet_column_statistics(metadata, "block_number").unwrap();
+        assert_eq!(stats.min, Some(StatValue::Int(7)));
+        assert_eq!(stats.max, Some(StatValue::Int(3_000_000_001)));
+        assert_eq!(stats.null_count, Some(0));
+
+        let stats = get_parquet_column_statistics(metadata, "name").unwrap();
+        assert_eq!(stats.min, Some(StatValue::Bytes(b"a".to_vec())));
+        assert_eq!(stats.max, Some(StatValue::Bytes(b"c".to_vec())));
+        assert_eq!(stats.null_count, Some(1));
+
+        let stats = get_parquet_column_statistics(metadata, "price").unwrap();
+        assert_eq!(stats.min, Some(StatValue::Float(-2.0)));
+        assert_eq!(stats.max, Some(StatValue::Float(4.25)));
+
+        assert!(get_parquet_column_statistics(metadata, "missing").is_none());
+    }
+
+    #[test]
+    fn test_merge_column_statistics() {
+        let lhs = ColumnStatistics {
+            min: Some(StatValue::Int(5)),
+            max: Some(StatValue::Int(10)),
+            null_count: Some(1),
+        };
+        let rhs = ColumnStatistics {
+            min: Some(StatValue::Int(-3)),
+            max: Some(StatValue::Int(8)),
+            null_count: Some(2),
+        };
+        let merged = lhs.merge(&rhs);
+        assert_eq!(merged.min, Some(StatValue::Int(-3)));
+        assert_eq!(merged.max, Some(StatValue::Int(10)));
+        assert_eq!(merged.null_count, Some(3));
+
+        // a missing bound on either side makes the merged bound unknown
+        let unknown = ColumnStatistics::default();
+        assert_eq!(lhs.merge(&unknown), ColumnStatistics::default());
+    }
+}
@@ -1,12 +1,15 @@
 use crate::TblError;
 use futures::stream::{self, StreamExt};
+use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions};
 use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
 use polars::prelude::*;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
 
 /// get the number of rows in a parquet file
 pub async fn get_parquet_row_count(path: &std::path::Path) -> Result<u64, TblError> {
-    let file = tokio::fs::File::open(path).await?;
+    let file = super::parquet_reader::open_parquet_file(path).await?;
     let builder = ParquetRecordBatchStreamBuilder::new(file)
         .await?
         .with_batch_size(1);
@@ -27,11 +30,106 @@ pub async fn get_parquet_row_counts(paths: &[&std::path::Path]) -> Result<Vec<u6
         .collect::<Result<Vec<u64>, TblError>>()
 }
 
+/// file selection predicates that are evaluated from metadata alone
+#[derive(Clone, Default)]
+pub struct FileSelection {
+    /// minimum number of rows
+    pub min_rows: Option<u64>,
+    /// maximum number of rows
+    pub max_rows: Option<u64>,
+    /// minimum number of bytes on disk
+    pub min_bytes: Option<u64>,
+    /// minimum modification time
+    pub modified_since: Option<SystemTime>,
+    /// columns that must be present
+    pub has_columns: Vec<String>,
+    /// columns that must be absent
+    pub missing_columns: Vec<String>,
+    /// schema that must be matched exactly
+    pub schema: Option<Arc<Schema>>,
+}
+
+impl FileSelection {
+    /// return true if no predicates are set
+    pub fn is_empty(&self) -> bool {
+        self.min_rows.is_none()
+            && self.max_rows.is_none()
+            && self.min_bytes.is_none()
+            && self.modified_since.is_none()
+            && self.has_columns.is_empty()
+            && self.missing_columns.is_empty()
+            && self.schema.is_none()
+    }
+
+    fn uses_schema(&self) -> bool {
+        !self.has_columns.is_empty() || !self.missing_columns.is_empty() || self.schema.is_some()
+    }
+}
+
+/// keep only files whose metadata satisfies selection
+///
+/// byte and modification time predicates are checked before any parquet footer is read
+pub async fn select_files_by_metadata(
+    paths: Vec<PathBuf>,
+    selection: &FileSelection,
+) -> Result<Vec<PathBuf>, TblError> {
+    let paths = crate::filesystem::filter_paths_by_file_stats(
+        paths,
+        selection.min_bytes,
+        selection.modified_since,
+    )
+    .await?;
+    if selection.min_rows.is_none() && selection.max_rows.is_none() && !selection.uses_schema() {
+        return Ok(paths);
+    }
+
+    let keep = stream::iter(paths.iter())
+        .map(|path| file_matches_selection(path, selection))
+        .buffered(10)
+        .collect::<Vec<Result<bool, TblError>>>()
+        .await;
+    let mut selected = Vec::new();
+    for (path, keep) in paths.into_iter().zip(keep) {
+        if keep? {
+            selected.push(path);
+        }
+    }
+    Ok(selected)
+}
+
+async fn file_matches_selection(
+    path: &std::path::Path,
+    selection: &FileSelection,
+) -> Result<bool, TblError> {
+    if selection.min_rows.is_some() || selection.max_rows.is_some() {
+        let n_rows = get_parquet_row_count(path).await?;
+        if selection.min_rows.map(|min| n_rows < min).unwrap_or(false)
+            || selection.max_rows.map(|max| n_rows > max).unwrap_or(false)
+        {
+            return Ok(false);
+        }
+    }
+    if selection.uses_schema() {
+        let schema = get_parquet_schema(path).await?;
+        if !selection.has_columns.iter().all(|c| schema.contains(c))
+            || selection.missing_columns.iter().any(|c| schema.contains(c))
+        {
+            return Ok(false);
+        }
+        if let Some(target) = &selection.schema {
+            if schema.as_ref() != target.as_ref() {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
 /// get parquet schema
 pub async fn get_parquet_schema(path: &std::path::Path) -> Result<Arc<Schema>, TblError> {
     let path = path.to_path_buf();
     tokio::task::spawn_blocking(move || {
-        let scan_args = ScanArgsParquet::default();
+        let scan_args = super::parquet_scan::get_file_scan_args();
         let mut lf = LazyFrame::scan_parquet(path, scan_args)?;
         let schema = lf.schema()?;
         Ok(schema)
@@ -88,12 +186,11 @@ pub struct TabularColumnSummary {
 
 /// get summary of parquet file
 pub async fn get_parquet_summary(path: &std::path::Path) -> Result<TabularSummary, TblError> {
-    let metadata = std::fs::metadata(path)?;
-    let n_bytes_compressed = metadata.len();
-    let n_rows = get_parquet_row_count(path).await?;
+    let n_bytes_compressed = crate::filesystem::get_file_size(path).await?;
+    let parquet_metadata = get_parquet_metadata(path).await?;
+    let n_rows = parquet_metadata.file_metadata().num_rows() as u64;
     let schema = get_parquet_schema(path).await?;
 
-    let parquet_metadata = get_parquet_metadata(path).await?;
     let columns = get_parquet_column_summaries(parquet_metadata.clone()).await?;
     let n_bytes_uncompressed = get_parquet_n_bytes_uncompressed(parquet_metadata);
 
@@ -111,11 +208,23 @@ pub async fn get_parquet_summary(path: &std::path::Path) -> Result<TabularSummar
 pub async fn get_parquet_metadata(
     path: &std::path::Path,
 ) -> Result<std::sync::Arc<parquet::file::metadata::ParquetMetaData>, TblError> {
-    let file = tokio::fs::File::open(path).await?;
-    let builder = ParquetRecordBatchStreamBuilder::new(file)
-        .await?
-        .with_batch_size(1);
-    Ok(builder.metadata().clone())
+    load_parquet_metadata(path, ArrowReaderOptions::new()).await
+}
+
+/// get parquet file metadata, including page indexes if present
+pub async fn get_parquet_metadata_with_page_index(
+    path: &std::path::Path,
+) -> Result<std::sync::Arc<parquet::file::metadata::ParquetMetaData>, TblError> {
+    load_parquet_metadata(path, ArrowReaderOptions::new().with_page_index(true)).await
+}
+
+async fn load_parquet_metadata(
+    path: &std::path::Path,
+    options: ArrowReaderOptions,
+) -> Result<std::sync::Arc<parquet::file::metadata::ParquetMetaData>, TblError> {
+    let mut file = super::parquet_reader::open_parquet_file(path).await?;
+    let reader_metadata = ArrowReaderMetadata::load_async(&mut file, options).await?;
+    Ok(reader_metadata.metadata().clone())
 }
 
 /// get parquet uncompressed bytes
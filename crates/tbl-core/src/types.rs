@@ -27,6 +27,14 @@ pub enum TblError {
     #[error(transparent)]
     ArrowError(#[from] arrow::error::ArrowError),
 
+    /// Error wrapper for thrift errors.
+    #[error(transparent)]
+    ThriftError(#[from] thrift::Error),
+
+    /// Error wrapper for object store errors.
+    #[error(transparent)]
+    ObjectStoreError(#[from] object_store::Error),
+
     /// Error wrapper for schema errors.
     #[error("Schema error: {0}")]
     SchemaError(String),
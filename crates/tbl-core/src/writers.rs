@@ -0,0 +1,243 @@
+use crate::TblError;
+use polars::prelude::*;
+use std::path::Path;
+
+/// format of a tabular output file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFileFormat {
+    /// parquet
+    Parquet,
+    /// comma separated values
+    Csv,
+    /// tab separated values
+    Tsv,
+    /// json array of rows
+    Json,
+    /// json lines, one row per line
+    Jsonl,
+    /// arrow ipc file
+    Ipc,
+}
+
+impl OutputFileFormat {
+    /// parse format name or file extension
+    pub fn from_name(name: &str) -> Option<OutputFileFormat> {
+        match name.to_lowercase().as_str() {
+            "parquet" | "pq" => Some(OutputFileFormat::Parquet),
+            "csv" => Some(OutputFileFormat::Csv),
+            "tsv" => Some(OutputFileFormat::Tsv),
+            "json" => Some(OutputFileFormat::Json),
+            "jsonl" | "ndjson" => Some(OutputFileFormat::Jsonl),
+            "ipc" | "arrow" | "feather" => Some(OutputFileFormat::Ipc),
+            _ => None,
+        }
+    }
+
+    /// detect format from file extension, none if extension is missing or unknown
+    pub fn from_path(path: &Path) -> Option<OutputFileFormat> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(OutputFileFormat::from_name)
+    }
+
+    /// file extension written for format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFileFormat::Parquet => "parquet",
+            OutputFileFormat::Csv => "csv",
+            OutputFileFormat::Tsv => "tsv",
+            OutputFileFormat::Json => "json",
+            OutputFileFormat::Jsonl => "jsonl",
+            OutputFileFormat::Ipc => "arrow",
+        }
+    }
+}
+
+/// options for writing tabular files
+#[derive(Clone, Debug, Default)]
+pub struct FileWriteOptions {
+    /// options of csv and tsv outputs
+    pub csv: CsvWriterOptions,
+    /// parquet compression, polars default if none
+    pub compression: Option<ParquetCompression>,
+    /// rows per parquet row group, polars default if none
+    pub row_group_size: Option<usize>,
+    /// parquet columns to write bloom filters for
+    pub bloom_filter_columns: Vec<String>,
+    /// write parquet page indexes, also written when there are bloom filter columns
+    pub page_index: bool,
+}
+
+/// write lazyframe to file, streaming when the format and query allow it
+pub fn write_lf_to_file(
+    lf: LazyFrame,
+    path: &Path,
+    format: OutputFileFormat,
+    options: &FileWriteOptions,
+) -> Result<(), TblError> {
+    // sinks are still missing some options and plans, so if they fail use eager writers
+    match format {
+        OutputFileFormat::Csv | OutputFileFormat::Tsv => {
+            if lf.clone().sink_csv(path, options.csv.clone()).is_err() {
+                let file = std::fs::File::create(path)?;
+                let serialize = options.csv.serialize_options.clone();
+                CsvWriter::new(file)
+                    .include_header(options.csv.include_header)
+                    .with_separator(serialize.separator)
+                    .with_datetime_format(serialize.datetime_format)
+                    .with_date_format(serialize.date_format)
+                    .finish(&mut lf.collect()?)?;
+            }
+        }
+        OutputFileFormat::Json => {
+            let file = std::fs::File::create(path)?;
+            JsonWriter::new(file)
+                .with_json_format(JsonFormat::Json)
+                .finish(&mut lf.collect()?)?;
+        }
+        OutputFileFormat::Jsonl => {
+            if lf
+                .clone()
+                .sink_json(path, JsonWriterOptions::default())
+                .is_err()
+            {
+                let file = std::fs::File::create(path)?;
+                JsonWriter::new(file)
+                    .with_json_format(JsonFormat::JsonLines)
+                    .finish(&mut lf.collect()?)?;
+            }
+        }
+        OutputFileFormat::Ipc => {
+            if lf
+                .clone()
+                .sink_ipc(path, IpcWriterOptions::default())
+                .is_err()
+            {
+                let file = std::fs::File::create(path)?;
+                IpcWriter::new(file).finish(&mut lf.collect()?)?;
+            }
+        }
+        OutputFileFormat::Parquet
+            if options.page_index || !options.bloom_filter_columns.is_empty() =>
+        {
+            crate::parquet::write_parquet_with_bloom_filters(
+                &lf.collect()?,
+                path,
+                &options.bloom_filter_columns,
+                options.compression.unwrap_or_default(),
+                options.row_group_size,
+            )?;
+        }
+        OutputFileFormat::Parquet => {
+            let mut parquet_options = ParquetWriteOptions::default();
+            if let Some(compression) = options.compression {
+                parquet_options.compression = compression;
+            }
+            parquet_options.row_group_size =
+                options.row_group_size.or(parquet_options.row_group_size);
+            let compression = parquet_options.compression;
+            if lf.clone().sink_parquet(path, parquet_options).is_err() {
+                let file = std::fs::File::create(path)?;
+                let writer = ParquetWriter::new(file)
+                    .with_compression(compression)
+                    .with_row_group_size(options.row_group_size)
+                    .with_statistics(StatisticsOptions {
+                        min_value: true,
+                        max_value: true,
+                        distinct_count: true,
+                        null_count: true,
+                    });
+                writer.finish(&mut lf.collect()?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_output_file_format_from_path() {
+        let cases = [
+            ("data.parquet", Some(OutputFileFormat::Parquet)),
+            ("data.PQ", Some(OutputFileFormat::Parquet)),
+            ("data.csv", Some(OutputFileFormat::Csv)),
+            ("data.tsv", Some(OutputFileFormat::Tsv)),
+            ("data.json", Some(OutputFileFormat::Json)),
+            ("data.ndjson", Some(OutputFileFormat::Jsonl)),
+            ("data.feather", Some(OutputFileFormat::Ipc)),
+            ("data.txt", None),
+            ("data", None),
+        ];
+        for (path, format) in cases.into_iter() {
+            assert_eq!(OutputFileFormat::from_path(Path::new(path)), format);
+        }
+        for format in [
+            OutputFileFormat::Parquet,
+            OutputFileFormat::Csv,
+            OutputFileFormat::Tsv,
+            OutputFileFormat::Json,
+            OutputFileFormat::Jsonl,
+            OutputFileFormat::Ipc,
+        ] {
+            assert_eq!(
+                OutputFileFormat::from_name(format.extension()),
+                Some(format)
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_lf_to_file() {
+        let dir = TempDir::new().unwrap();
+        let df = df!(
+            "block_number" => [1u32, 2, 3],
+            "name" => ["a", "b", "c"]
+        )
+        .unwrap();
+        let mut tsv_options = FileWriteOptions::default();
+        tsv_options.csv.serialize_options.separator = b'\t';
+        let cases = [
+            (OutputFileFormat::Parquet, FileWriteOptions::default()),
+            (OutputFileFormat::Csv, FileWriteOptions::default()),
+            (OutputFileFormat::Tsv, tsv_options),
+            (OutputFileFormat::Json, FileWriteOptions::default()),
+            (OutputFileFormat::Jsonl, FileWriteOptions::default()),
+            (OutputFileFormat::Ipc, FileWriteOptions::default()),
+        ];
+        for (format, options) in cases.into_iter() {
+            let path: PathBuf = dir.path().join(format!("data.{}", format.extension()));
+            write_lf_to_file(df.clone().lazy(), &path, format, &options).unwrap();
+            let file = std::fs::File::open(&path).unwrap();
+            let read = match format {
+                OutputFileFormat::Parquet => ParquetReader::new(file).finish().unwrap(),
+                OutputFileFormat::Csv | OutputFileFormat::Tsv => CsvReadOptions::default()
+                    .with_parse_options(
+                        CsvParseOptions::default()
+                            .with_separator(options.csv.serialize_options.separator),
+                    )
+                    .into_reader_with_file_handle(file)
+                    .finish()
+                    .unwrap(),
+                OutputFileFormat::Json => JsonReader::new(file).finish().unwrap(),
+                OutputFileFormat::Jsonl => JsonReader::new(file)
+                    .with_json_format(JsonFormat::JsonLines)
+                    .finish()
+                    .unwrap(),
+                OutputFileFormat::Ipc => IpcReader::new(file).finish().unwrap(),
+            };
+            assert_eq!(read.shape(), (3, 2), "{:?}", format);
+            assert_eq!(read.get_column_names(), df.get_column_names());
+            let names = read
+                .column("name")
+                .unwrap()
+                .cast(&DataType::String)
+                .unwrap();
+            assert_eq!(names.str().unwrap().get(2), Some("c"), "{:?}", format);
+        }
+    }
+}